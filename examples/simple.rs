@@ -3,13 +3,13 @@ use std::time::Duration;
 
 use pbar::ProgressBar;
 
-fn main() {
+fn main() -> pbar::Result<()> {
     let count = 1000;
-    let mut pbar = ProgressBar::stdout(count);
+    let pbar = ProgressBar::stdout(count);
     pbar.set_title("Simple:");
     for _ in 0..count {
-        pbar.increase();
+        pbar.inc()?;
         thread::sleep(Duration::from_millis(10));
     }
-    pbar.finish_with_msg("Done...");
+    pbar.finish_with_msg("Done...")
 }
@@ -1,23 +1,28 @@
 use chrono::prelude::*;
-use pbar::{ProgressBar, ProgressBarStyle};
+use pbar::{BarSymbols, BarWidth, ProgressBar, ProgressBarStyle};
 
 fn leap_or_normal(year: u32) -> u16 {
-    if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 {
+    if (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400) {
         366
     } else {
         365
     }
 }
 
-fn main() {
+fn main() -> pbar::Result<()> {
     let dt = Local::now();
     let days = leap_or_normal(dt.year() as u32);
-    let mut pbar = ProgressBar::stdout(days as u64);
+    let pbar = ProgressBar::stdout(days as u64);
 
     let mut style = ProgressBarStyle::customizable();
-    style.counter(None, None).percent().bar(" ██░ ", Some(30));
+    let symbols = BarSymbols::new("", "█", "█", "░", "");
+    style
+        .counter(None, None)
+        .percent()
+        .bar_symbols(symbols, BarWidth::Fixed(30))?;
 
     pbar.set_title(&format!("{} year progress:", dt.year())[..])
-        .set_style(style);
-    pbar.set(dt.ordinal() as u64, true);
+        .set_style(style)?;
+    pbar.set(dt.ordinal() as u64, true)?;
+    Ok(())
 }
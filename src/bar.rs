@@ -1,9 +1,13 @@
-use std::io;
-use std::iter::repeat;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard, Weak};
+use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::color::*;
+use crate::error::*;
+use crate::estimator::*;
 use crate::format::*;
+use crate::io::*;
 use crate::style::*;
 use crate::term::*;
 use crate::util::*;
@@ -13,9 +17,97 @@ pub struct ProgressBarDrawInfo {
     pub done: bool,
 }
 
+/// Events sent by the progress bars on channel targets to MultiProgressBar.
+pub enum ProgressBarEvent {
+    /// Redraw the line of the progress bar with the index.
+    Draw(usize, ProgressBarDrawInfo),
+    /// Print a permanent line above the progress bars.
+    Println(String),
+    /// Erase the progress bars, acknowledge with the sender,
+    /// and redraw them once the receiver gets a message or disconnects.
+    Suspend(mpsc::Sender<()>, mpsc::Receiver<()>),
+}
+
+/// How the progress bars are drawn on a terminal that is not interactive,
+/// like the output redirected to a file or a pipe, or `TERM=dumb`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonInteractiveMode {
+    /// Draw nothing but the lines printed by `println`, the default mode.
+    #[default]
+    Hidden,
+    /// Print the progress as full lines, like the log target.
+    Log(LogMode),
+}
+
+/// How often the progress is printed as a full line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogInterval {
+    /// Print a line every duration.
+    Time(Duration),
+    /// Print a line every N percent of the total,
+    /// an indeterminate progress is only printed when it starts and once it's finished.
+    Percent(u8),
+}
+
+/// How the progress is printed as full lines, without cursor movements or carriage returns.
+///
+/// A line is printed every interval and once the progress is finished,
+/// the bar component of the style is skipped unless `bar` is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogMode {
+    pub interval: LogInterval,
+    pub bar: bool,
+}
+
+impl LogMode {
+    pub fn new(interval: LogInterval) -> LogMode {
+        LogMode {
+            interval,
+            bar: false,
+        }
+    }
+}
+
 pub enum ProgressBarTargetKind {
     Term(Term),
-    Channel(usize, mpsc::Sender<(usize, ProgressBarDrawInfo)>),
+    /// The terminal printing the progress as full lines, see `LogMode`.
+    Log(Term, LogMode),
+    /// The progress bar attached to MultiProgressBar, which draws on the shared target if any.
+    Channel(
+        usize,
+        mpsc::Sender<ProgressBarEvent>,
+        Option<Arc<MultiTarget>>,
+    ),
+}
+
+/// The target of MultiProgressBar, shared with the progress bars attached to it,
+/// so that they're rendered for the terminal that MultiProgressBar draws on.
+pub struct MultiTarget {
+    pub(crate) target: ProgressBarTarget,
+    pub(crate) non_interactive: Mutex<NonInteractiveMode>,
+    // whether MultiProgressBar is receiving the events of the attached progress bars.
+    pub(crate) listening: Mutex<bool>,
+}
+
+impl MultiTarget {
+    pub(crate) fn new(target: ProgressBarTarget) -> MultiTarget {
+        MultiTarget {
+            target,
+            non_interactive: Mutex::new(NonInteractiveMode::default()),
+            listening: Mutex::new(false),
+        }
+    }
+
+    pub(crate) fn listening(&self) -> MutexGuard<'_, bool> {
+        self.listening.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn non_interactive(&self) -> NonInteractiveMode {
+        *self
+            .non_interactive
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
 }
 
 pub struct ProgressBarTarget {
@@ -35,349 +127,1827 @@ impl ProgressBarTarget {
         }
     }
 
-    pub fn channel(
+    /// Print the progress as full lines on stdout, for batch jobs and log files.
+    pub fn log_stdout(mode: LogMode) -> ProgressBarTarget {
+        ProgressBarTarget {
+            kind: ProgressBarTargetKind::Log(Term::stdout(), mode),
+        }
+    }
+
+    /// Print the progress as full lines on stderr, for batch jobs and log files.
+    pub fn log_stderr(mode: LogMode) -> ProgressBarTarget {
+        ProgressBarTarget {
+            kind: ProgressBarTargetKind::Log(Term::stderr(), mode),
+        }
+    }
+
+    pub fn channel(index: usize, tx: mpsc::Sender<ProgressBarEvent>) -> ProgressBarTarget {
+        ProgressBarTarget {
+            kind: ProgressBarTargetKind::Channel(index, tx, None),
+        }
+    }
+
+    /// The channel target of a progress bar attached to MultiProgressBar.
+    pub(crate) fn attached(
         index: usize,
-        tx: mpsc::Sender<(usize, ProgressBarDrawInfo)>,
+        tx: mpsc::Sender<ProgressBarEvent>,
+        multi: Arc<MultiTarget>,
     ) -> ProgressBarTarget {
         ProgressBarTarget {
-            kind: ProgressBarTargetKind::Channel(index, tx),
+            kind: ProgressBarTargetKind::Channel(index, tx, Some(multi)),
         }
     }
 
-    pub fn terminal_width(&self) -> usize {
+    /// Return whether the target can redraw the progress bar in place,
+    /// channel targets follow the target of MultiProgressBar.
+    pub fn is_interactive(&self) -> bool {
         match self.kind {
-            ProgressBarTargetKind::Term(ref term) => {
-                term.terminal_size().unwrap_or(TERM_DEFAULT_WINDOW).0
-            }
-            _ => 0,
+            ProgressBarTargetKind::Term(ref term) => term.is_interactive(),
+            ProgressBarTargetKind::Log(..) => false,
+            ProgressBarTargetKind::Channel(_, _, ref multi) => multi
+                .as_ref()
+                .is_none_or(|multi| multi.target.is_interactive()),
+        }
+    }
+
+    /// Return how to draw on the target if it's not interactive,
+    /// the log target has its own mode, the channel targets use the mode of MultiProgressBar,
+    /// the other ones use `mode`.
+    fn non_interactive_mode(&self, mode: NonInteractiveMode) -> Option<NonInteractiveMode> {
+        match self.kind {
+            ProgressBarTargetKind::Log(_, mode) => Some(NonInteractiveMode::Log(mode)),
+            _ if self.is_interactive() => None,
+            ProgressBarTargetKind::Channel(_, _, Some(ref multi)) => Some(multi.non_interactive()),
+            _ => Some(mode),
+        }
+    }
+
+    fn is_channel(&self) -> bool {
+        matches!(self.kind, ProgressBarTargetKind::Channel(..))
+    }
+
+    /// Return whether the target draws colors and attributes,
+    /// channel targets follow the target of MultiProgressBar, or stdout if not attached.
+    pub fn supports_color(&self) -> bool {
+        match self.kind {
+            ProgressBarTargetKind::Term(ref term) => term.supports_color(),
+            ProgressBarTargetKind::Log(..) => false,
+            ProgressBarTargetKind::Channel(_, _, Some(ref multi)) => multi.target.supports_color(),
+            ProgressBarTargetKind::Channel(_, _, None) => Term::stdout().supports_color(),
         }
     }
 
-    pub fn move_cursor_up(&self, n: usize) {
+    /// Channel targets follow the target of MultiProgressBar, or stdout if not attached.
+    ///
+    /// It's queried again for every frame, so that it follows the terminal size.
+    pub fn terminal_width(&self) -> usize {
+        let size = match self.kind {
+            ProgressBarTargetKind::Term(ref term) | ProgressBarTargetKind::Log(ref term, _) => {
+                term.terminal_size()
+            }
+            ProgressBarTargetKind::Channel(_, _, Some(ref multi)) => {
+                return multi.target.terminal_width();
+            }
+            ProgressBarTargetKind::Channel(_, _, None) => Term::stdout().terminal_size(),
+        };
+        // some pseudo terminals report no size at all.
+        size.filter(|&(width, _)| width > 0)
+            .unwrap_or(TERM_DEFAULT_WINDOW)
+            .0
+    }
+
+    pub fn move_cursor_up(&self, n: usize) -> Result<()> {
         if let ProgressBarTargetKind::Term(ref term) = self.kind {
-            term.move_cursor_up(n).unwrap();
+            term.move_cursor_up(n)?;
         }
+        Ok(())
     }
 
-    /// Special for MultiProgressBar.
-    pub fn draw(&self, line: &str) -> io::Result<()> {
+    pub fn clear_line(&self) -> Result<()> {
         if let ProgressBarTargetKind::Term(ref term) = self.kind {
-            term.write_target(line.as_bytes()).unwrap();
+            term.clear_line()?;
         }
         Ok(())
     }
 
-    /// Special for ProgressBar.
-    pub fn handle_draw_info(&self, info: ProgressBarDrawInfo) -> io::Result<()> {
+    /// Special for MultiProgressBar, and the full lines of the progress bars,
+    /// which are printed by MultiProgressBar for the channel targets.
+    pub fn draw(&self, line: &str) -> Result<()> {
+        match self.kind {
+            ProgressBarTargetKind::Term(ref term) | ProgressBarTargetKind::Log(ref term, _) => {
+                term.write_target(line.as_bytes())?;
+            }
+            ProgressBarTargetKind::Channel(_, ref tx, _) => {
+                let line = line.strip_suffix('\n').unwrap_or(line);
+                tx.send(ProgressBarEvent::Println(line.to_string()))
+                    .map_err(|_| Error::Disconnected)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Special for ProgressBar, erase the progress bar from the terminal.
+    ///
+    /// For the channel target, it asks MultiProgressBar to erase all progress bars,
+    /// and returns the receiver of its acknowledgement and the sender to let them be redrawn.
+    /// Nothing is asked if the attached progress bars aren't being drawn by `join`.
+    pub fn suspend(&self) -> Result<Option<(mpsc::Receiver<()>, mpsc::Sender<()>)>> {
         match self.kind {
             ProgressBarTargetKind::Term(ref term) => {
-                term.write_target(info.line.as_bytes()).unwrap();
+                term.clear_line()?;
+                Ok(None)
+            }
+            ProgressBarTargetKind::Log(..) => Ok(None),
+            ProgressBarTargetKind::Channel(_, ref tx, ref multi) => {
+                let listening = multi.as_ref().map(|multi| multi.listening());
+                if listening.as_ref().is_some_and(|listening| !**listening) {
+                    return Ok(None);
+                }
+                let (ack_tx, ack_rx) = mpsc::channel();
+                let (resume_tx, resume_rx) = mpsc::channel();
+                tx.send(ProgressBarEvent::Suspend(ack_tx, resume_rx))
+                    .map_err(|_| Error::Disconnected)?;
+                // MultiProgressBar handles the sent event even if it stops listening now.
+                drop(listening);
+                Ok(Some((ack_rx, resume_tx)))
+            }
+        }
+    }
+
+    /// Special for ProgressBar, print `msg` as a permanent line above the progress bar,
+    /// then redraw the progress bar with `line`, or print `msg` below if `line` is `None`.
+    pub fn println(&self, msg: &str, line: Option<&str>) -> Result<()> {
+        match self.kind {
+            ProgressBarTargetKind::Term(ref term) => match line {
+                Some(line) => {
+                    term.clear_line()?;
+                    term.write_target(format!("{}\n{}", msg, line).as_bytes())?;
+                }
+                None => term.write_target(format!("\n{}", msg).as_bytes())?,
+            },
+            ProgressBarTargetKind::Log(ref term, _) => {
+                term.write_target(format!("{}\n", msg).as_bytes())?;
+            }
+            ProgressBarTargetKind::Channel(_, ref tx, _) => {
+                tx.send(ProgressBarEvent::Println(msg.to_string()))
+                    .map_err(|_| Error::Disconnected)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Special for ProgressBar.
+    pub fn handle_draw_info(&self, info: ProgressBarDrawInfo) -> Result<()> {
+        match self.kind {
+            ProgressBarTargetKind::Term(ref term) | ProgressBarTargetKind::Log(ref term, _) => {
+                term.write_target(info.line.as_bytes())?;
             }
-            ProgressBarTargetKind::Channel(index, ref tx) => {
-                tx.send((index, info)).unwrap();
+            ProgressBarTargetKind::Channel(index, ref tx, _) => {
+                tx.send(ProgressBarEvent::Draw(index, info))
+                    .map_err(|_| Error::Disconnected)?;
             }
         }
         Ok(())
     }
 }
 
-struct ProgressBarContext {
+struct ProgressBarState {
+    target: ProgressBarTarget,
+    style: ProgressBarStyle,
+    // whether the style is still the default one, not set by user.
+    default_style: bool,
+    // whether the locale is UTF-8, otherwise the smooth bar falls back to ASCII.
+    unicode: bool,
+    // whether the text styles of the components are drawn.
+    colors: bool,
     width: usize,
-    current: u64,
-    total: u64,
+    // whether the width is set by user, otherwise it follows the terminal size.
+    fixed_width: bool,
     title: String,
+    message: String,
+    estimator: Box<dyn Estimator>,
+    // number of times the progress bar has been rendered, drives animations.
+    ticks: u64,
+    // the background thread redrawing the progress bar steadily.
+    ticker: Option<thread::Thread>,
+    // the interval of the steady tick, kept to restart the ticker on reset.
+    tick_interval: Option<Duration>,
+    // number of running `suspend` closures, nothing is drawn until it's back to zero.
+    suspended: usize,
+    // dropped to let MultiProgressBar redraw after suspending a channel target.
+    resume: Option<mpsc::Sender<()>>,
+    error_policy: ErrorPolicy,
+    non_interactive: NonInteractiveMode,
+    // the last line printed in `NonInteractiveMode::Log`.
+    logged: Option<LoggedLine>,
+    done: bool,
+}
+
+struct LoggedLine {
+    time: Instant,
+    // the percent step of `LogInterval::Percent`.
+    step: Option<u64>,
+    finished: bool,
+}
+
+const NOT_PAUSED: u64 = u64::MAX;
+const NO_TOTAL: u64 = u64::MAX;
+// the shortest interval of the steady tick, so that the ticker never spins.
+const MIN_TICK_INTERVAL: Duration = Duration::from_millis(1);
+
+struct ProgressBarContext {
+    current: AtomicU64,
+    // `NO_TOTAL` if the progress bar is indeterminate, the totals are clamped below it.
+    total: AtomicU64,
+    finished: AtomicBool,
 
     start_time: Instant,
-    last_refresh_time: Instant,
-    refresh_rate: Duration,
+    // nanoseconds elapsed from `start_time` to the (re)start of the progress.
+    started: AtomicU64,
+    // nanoseconds elapsed from `start_time` to the last refresh.
+    last_refresh: AtomicU64,
+    // nanoseconds elapsed from `start_time` to the current pause, or `NOT_PAUSED`.
+    paused_since: AtomicU64,
+    // total nanoseconds of the finished pauses.
+    paused_total: AtomicU64,
+    // minimal interval between two refreshes, in nanoseconds.
+    refresh_rate: AtomicU64,
+
+    state: Mutex<ProgressBarState>,
 }
 
 impl ProgressBarContext {
+    fn new(target: ProgressBarTarget, width: usize, total: Option<u64>) -> ProgressBarContext {
+        let colors = target.supports_color();
+        ProgressBarContext {
+            current: AtomicU64::new(0),
+            total: AtomicU64::new(total.map_or(NO_TOTAL, |total| total.min(NO_TOTAL - 1))),
+            finished: AtomicBool::new(false),
+            start_time: Instant::now(),
+            started: AtomicU64::new(0),
+            last_refresh: AtomicU64::new(0),
+            paused_since: AtomicU64::new(NOT_PAUSED),
+            paused_total: AtomicU64::new(0),
+            refresh_rate: AtomicU64::new(500_000_000),
+            state: Mutex::new(ProgressBarState {
+                target,
+                style: ProgressBarStyle::default(),
+                default_style: true,
+                unicode: is_unicode_locale(),
+                colors,
+                width,
+                fixed_width: false,
+                title: String::new(),
+                message: String::new(),
+                estimator: Box::new(AverageEstimator::new()),
+                ticks: 0,
+                ticker: None,
+                tick_interval: None,
+                suspended: 0,
+                resume: None,
+                error_policy: ErrorPolicy::default(),
+                non_interactive: NonInteractiveMode::default(),
+                logged: None,
+                done: false,
+            }),
+        }
+    }
+
     pub fn is_finish(&self) -> bool {
-        self.current >= self.total
+        if self.finished.load(Ordering::Relaxed) {
+            return true;
+        }
+        match self.current() {
+            (current, Some(total)) => current >= total,
+            (_, None) => false,
+        }
     }
 
-    pub fn current(&self) -> (u64, u64) {
-        (self.current, self.total)
+    pub fn current(&self) -> (u64, Option<u64>) {
+        (self.current.load(Ordering::Relaxed), self.total())
     }
 
-    pub fn percent(&self) -> f64 {
-        match (self.current, self.total) {
-            (_, 0) => 1.0,
-            (0, _) => 0.0,
-            (current, total) => current as f64 / total as f64,
+    pub fn total(&self) -> Option<u64> {
+        match self.total.load(Ordering::Relaxed) {
+            NO_TOTAL => None,
+            total => Some(total),
         }
     }
 
-    pub fn speed(&self) -> f64 {
-        self.current as f64 / duration_to_secs(self.time_elapsed())
+    /// Return `None` if the progress bar is indeterminate and not finished.
+    pub fn percent(&self) -> Option<f64> {
+        match self.current() {
+            _ if self.is_finish() => Some(1.0),
+            (_, None) => None,
+            (_, Some(0)) => Some(1.0),
+            (0, _) => Some(0.0),
+            (current, Some(total)) => Some(current as f64 / total as f64),
+        }
     }
 
     pub fn time_elapsed(&self) -> Duration {
-        self.last_refresh_time.duration_since(self.start_time)
+        let now = self.last_refresh.load(Ordering::Acquire);
+        let paused = self.started.load(Ordering::Acquire)
+            + self.paused_total.load(Ordering::Acquire)
+            + match self.paused_since.load(Ordering::Acquire) {
+                NOT_PAUSED => 0,
+                since => now.saturating_sub(since),
+            };
+        Duration::from_nanos(now.saturating_sub(paused))
     }
 
-    pub fn time_left(&self) -> Duration {
+    /// Return `None` if the progress bar is indeterminate and not finished,
+    /// or the estimated `speed` is not positive or so slow that the time left overflows.
+    pub fn time_left(&self, speed: f64) -> Option<Duration> {
         if self.is_finish() {
-            return Duration::new(0, 0);
+            return Some(Duration::new(0, 0));
         }
 
-        let d = self.time_elapsed();
-        secs_to_duration(
-            duration_to_secs(d) * (self.total - self.current) as f64 / self.current as f64,
-        )
+        let (current, total) = self.current();
+        let total = total?;
+        if !(speed > 0.0 && speed.is_finite()) {
+            return None;
+        }
+        secs_to_duration(total.saturating_sub(current) as f64 / speed)
     }
 
-    pub fn time_total(&self) -> Duration {
-        self.time_elapsed() + self.time_left()
+    /// Return `None` if the time left can't be estimated.
+    pub fn time_total(&self, speed: f64) -> Option<Duration> {
+        self.time_left(speed)
+            .and_then(|left| self.time_elapsed().checked_add(left))
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_since.load(Ordering::Acquire) != NOT_PAUSED
+    }
+
+    /// Callers must hold the state lock, so that pause and resume don't race.
+    fn pause(&self) {
+        if !self.is_paused() {
+            self.paused_since.store(self.now(), Ordering::Release);
+        }
+    }
+
+    /// Callers must hold the state lock, so that pause and resume don't race.
+    fn resume(&self) {
+        let since = self.paused_since.swap(NOT_PAUSED, Ordering::AcqRel);
+        if since != NOT_PAUSED {
+            self.paused_total
+                .fetch_add(self.now().saturating_sub(since), Ordering::AcqRel);
+        }
+    }
+
+    /// Restart the progress and the clock.
+    ///
+    /// Callers must hold the state lock, so that it doesn't race with pause and resume.
+    fn reset(&self) {
+        let now = self.now();
+        self.current.store(0, Ordering::Relaxed);
+        self.finished.store(false, Ordering::Relaxed);
+        self.started.store(now, Ordering::Release);
+        self.paused_since.store(NOT_PAUSED, Ordering::Release);
+        self.paused_total.store(0, Ordering::Release);
+        self.last_refresh.fetch_max(now, Ordering::AcqRel);
+    }
+
+    /// Mark the progress as finished, a determinate progress bar is filled up.
+    fn finish(&self) {
+        if let Some(total) = self.total() {
+            self.current.store(total, Ordering::Relaxed);
+        }
+        self.finished.store(true, Ordering::Relaxed);
+        self.claim_refresh(true);
+    }
+
+    /// Decide whether the caller should redraw the progress bar now.
+    ///
+    /// Only one of the threads racing for the same refresh slot wins,
+    /// forced and final refreshes always win.
+    fn claim_refresh(&self, is_force: bool) -> bool {
+        let now = self.now();
+        if is_force || self.is_finish() {
+            self.last_refresh.fetch_max(now, Ordering::AcqRel);
+            return true;
+        }
+
+        let last = self.last_refresh.load(Ordering::Acquire);
+        now.saturating_sub(last) >= self.refresh_rate.load(Ordering::Relaxed)
+            && self
+                .last_refresh
+                .compare_exchange(last, now, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+    }
+
+    // nanoseconds elapsed from `start_time`.
+    fn now(&self) -> u64 {
+        self.start_time.elapsed().as_nanos() as u64
+    }
+
+    fn state(&self) -> MutexGuard<'_, ProgressBarState> {
+        // a panic while drawing doesn't leave the state inconsistent.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for ProgressBarContext {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some(ticker) = state.ticker.take() {
+            ticker.unpark();
+        }
     }
 }
 
+/// A progress bar handle.
+///
+/// The handle is cheap to clone and can be shared between threads,
+/// all clones update and draw the same progress bar.
+#[derive(Clone)]
 pub struct ProgressBar {
-    target: ProgressBarTarget,
-    ctxt: ProgressBarContext,
-    style: ProgressBarStyle,
+    ctxt: Arc<ProgressBarContext>,
 }
 
 impl ProgressBar {
     /// Construct a progress bar with default style on stdout.
     pub fn stdout(total: u64) -> ProgressBar {
-        let target = ProgressBarTarget::stdout();
-        let width = target.terminal_width();
-        ProgressBar {
-            target,
-            ctxt: ProgressBarContext {
-                width,
-                current: 0,
-                total,
-                title: String::new(),
-                start_time: Instant::now(),
-                last_refresh_time: Instant::now(),
-                refresh_rate: Duration::from_millis(500),
-            },
-            style: ProgressBarStyle::default(),
-        }
+        ProgressBar::with_target(ProgressBarTarget::stdout(), Some(total))
     }
 
     /// Construct a progress bar with default style on stderr.
     pub fn stderr(total: u64) -> ProgressBar {
-        let target = ProgressBarTarget::stderr();
-        let width = target.terminal_width();
-        ProgressBar {
-            target,
-            ctxt: ProgressBarContext {
-                width,
-                current: 0,
-                total,
-                title: String::new(),
-                start_time: Instant::now(),
-                last_refresh_time: Instant::now(),
-                refresh_rate: Duration::from_millis(500),
-            },
-            style: ProgressBarStyle::default(),
-        }
+        ProgressBar::with_target(ProgressBarTarget::stderr(), Some(total))
+    }
+
+    /// Construct an indeterminate progress bar (without total) with default style on stdout.
+    pub fn stdout_indeterminate() -> ProgressBar {
+        ProgressBar::with_target(ProgressBarTarget::stdout(), None)
+    }
+
+    /// Construct an indeterminate progress bar (without total) with default style on stderr.
+    pub fn stderr_indeterminate() -> ProgressBar {
+        ProgressBar::with_target(ProgressBarTarget::stderr(), None)
     }
 
     /// Construct a progress bar with default style for MultiProgressBar specially.
+    /// The progress bar is indeterminate if `total` is `None`.
     pub fn channel(
-        total: u64,
+        total: Option<u64>,
         index: usize,
-        tx: mpsc::Sender<(usize, ProgressBarDrawInfo)>,
+        tx: mpsc::Sender<ProgressBarEvent>,
     ) -> ProgressBar {
-        let stdout = ProgressBarTarget::stdout();
-        let target = ProgressBarTarget::channel(index, tx);
-        let width = stdout.terminal_width();
+        ProgressBar::with_target(ProgressBarTarget::channel(index, tx), total)
+    }
+
+    /// Construct a progress bar with default style on the given target,
+    /// the progress bar is indeterminate if `total` is `None`.
+    pub fn with_target(target: ProgressBarTarget, total: Option<u64>) -> ProgressBar {
+        let width = target.terminal_width();
         ProgressBar {
-            target,
-            ctxt: ProgressBarContext {
-                width,
-                current: 0,
-                total,
-                title: String::new(),
-                start_time: Instant::now(),
-                last_refresh_time: Instant::now(),
-                refresh_rate: Duration::from_millis(500),
-            },
-            style: ProgressBarStyle::default(),
+            ctxt: Arc::new(ProgressBarContext::new(target, width, total)),
         }
     }
 
-    /// Set customize style for the progress bar.
-    pub fn set_style(&mut self, style: ProgressBarStyle) -> &mut Self {
-        self.style = style;
+    /// Set customize style for the progress bar, an invalid style is rejected.
+    pub fn set_style(&self, style: ProgressBarStyle) -> Result<&Self> {
+        style.validate()?;
+        let mut state = self.ctxt.state();
+        state.style = style;
+        state.default_style = false;
+        drop(state);
+        Ok(self)
+    }
+
+    /// Set how to handle the failures of drawing, default policy is `ErrorPolicy::Surface`.
+    pub fn set_error_policy(&self, policy: ErrorPolicy) -> &Self {
+        self.ctxt.state().error_policy = policy;
         self
     }
 
+    /// Set how to draw the progress bar if the terminal is not interactive,
+    /// default mode is `NonInteractiveMode::Hidden`.
+    ///
+    /// It doesn't affect the log target, which is drawn in its own mode.
+    pub fn set_non_interactive_mode(&self, mode: NonInteractiveMode) -> &Self {
+        self.ctxt.state().non_interactive = mode;
+        self
+    }
+
+    /// Wrap a reader, the progress bar is driven by the number of bytes read.
+    ///
+    /// The bytes style is used unless a customize style has been set.
+    pub fn wrap_read<R>(&self, reader: R) -> ProgressBarReader<R> {
+        self.use_bytes_style();
+        ProgressBarReader::new(reader, self.clone())
+    }
+
+    /// Wrap a writer, the progress bar is driven by the number of bytes written.
+    ///
+    /// The bytes style is used unless a customize style has been set.
+    pub fn wrap_write<W>(&self, writer: W) -> ProgressBarWriter<W> {
+        self.use_bytes_style();
+        ProgressBarWriter::new(writer, self.clone())
+    }
+
+    fn use_bytes_style(&self) {
+        let mut state = self.ctxt.state();
+        if state.default_style {
+            state.style = ProgressBarStyle::bytes_transfer();
+        }
+    }
+
     /// Set title of the progress bar.
-    pub fn set_title(&mut self, s: &str) -> &mut Self {
-        self.ctxt.title = s.to_string();
+    pub fn set_title(&self, s: &str) -> &Self {
+        self.ctxt.state().title = s.to_string();
         self
     }
 
-    /// Set width of the progress bar.
-    pub fn set_width(&mut self, width: usize) -> &mut Self {
-        self.ctxt.width = width;
+    /// Set message of the progress bar shown by the 'message' component,
+    /// the progress bar is redrawn subject to the refresh rate.
+    pub fn set_message(&self, s: &str) -> Result<()> {
+        self.ctxt.state().message = s.to_string();
+        self.update(false)
+    }
+
+    /// Set width of the progress bar, which follows the terminal size by default.
+    pub fn set_width(&self, width: usize) -> &Self {
+        let mut state = self.ctxt.state();
+        state.width = width;
+        state.fixed_width = true;
+        drop(state);
+        self
+    }
+
+    /// Set the estimator of the speed and the time left, default is `AverageEstimator`.
+    pub fn set_estimator<E: Estimator + 'static>(&self, estimator: E) -> &Self {
+        self.ctxt.state().estimator = Box::new(estimator);
+        self
+    }
+
+    /// Redraw the progress bar every `interval` in a background thread,
+    /// even if the progress is not updated.
+    ///
+    /// The thread stops when the progress bar is finished or dropped.
+    /// The interval is at least 1 millisecond.
+    pub fn enable_steady_tick(&self, interval: Duration) -> &Self {
+        let interval = interval.max(MIN_TICK_INTERVAL);
+        let mut state = self.ctxt.state();
+        if let Some(ticker) = state.ticker.take() {
+            ticker.unpark();
+        }
+        state.tick_interval = Some(interval);
+        if !state.done {
+            self.spawn_ticker(&mut state, interval);
+        }
+        drop(state);
         self
     }
 
+    /// Stop redrawing the progress bar in the background thread.
+    pub fn disable_steady_tick(&self) -> &Self {
+        let mut state = self.ctxt.state();
+        state.tick_interval = None;
+        if let Some(ticker) = state.ticker.take() {
+            ticker.unpark();
+        }
+        drop(state);
+        self
+    }
+
+    fn spawn_ticker(&self, state: &mut ProgressBarState, interval: Duration) {
+        let ctxt = Arc::downgrade(&self.ctxt);
+        let handle = thread::spawn(move || steady_tick(ctxt, interval));
+        state.ticker = Some(handle.thread().clone());
+    }
+
     /// Set refresh rate that drawing progress, default rate is 500ms.
-    pub fn set_refresh_rate(&mut self, rate: Duration) -> &mut Self {
-        self.ctxt.refresh_rate = rate;
+    pub fn set_refresh_rate(&self, rate: Duration) -> &Self {
+        self.ctxt
+            .refresh_rate
+            .store(rate.as_nanos() as u64, Ordering::Relaxed);
         self
     }
 
+    /// Return current value of the progress bar.
+    pub fn position(&self) -> u64 {
+        self.ctxt.current.load(Ordering::Relaxed)
+    }
+
+    /// Return total value of the progress bar, `None` if it's indeterminate.
+    pub fn total(&self) -> Option<u64> {
+        self.ctxt.total()
+    }
+
+    /// Set total value of the progress bar, an indeterminate progress bar becomes determinate.
+    pub fn set_total(&self, total: u64) -> Result<()> {
+        self.ctxt
+            .total
+            .store(total.min(NO_TOTAL - 1), Ordering::Relaxed);
+        self.update(false)
+    }
+
+    /// Add total value of the progress bar, for the work growing while it runs.
+    ///
+    /// An indeterminate progress bar becomes determinate with `value` as total.
+    pub fn inc_total(&self, value: u64) -> Result<()> {
+        let _ = self
+            .ctxt
+            .total
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| match total {
+                NO_TOTAL => Some(value.min(NO_TOTAL - 1)),
+                total => Some(total.saturating_add(value).min(NO_TOTAL - 1)),
+            });
+        self.update(false)
+    }
+
+    /// Reset the progress and restart the clock, so that the progress bar can be reused.
+    ///
+    /// The steady tick stopped by finishing is restarted.
+    pub fn reset(&self) -> Result<()> {
+        let mut state = self.ctxt.state();
+        self.ctxt.reset();
+        state.estimator.reset();
+        state.ticks = 0;
+        state.logged = None;
+        state.done = false;
+        if let (None, Some(interval)) = (&state.ticker, state.tick_interval) {
+            self.spawn_ticker(&mut state, interval);
+        }
+        self.draw(&mut state, None, false)
+    }
+
     /// Set current value of the progress bar.
-    pub fn set(&mut self, value: u64, is_force: bool) -> u64 {
-        self.ctxt.current = value;
-        self.update(is_force);
-        self.ctxt.current
+    pub fn set(&self, value: u64, is_force: bool) -> Result<u64> {
+        self.ctxt.current.store(value, Ordering::Relaxed);
+        self.update(is_force)?;
+        Ok(value)
     }
 
-    /// Add current value of the progress bar .
-    pub fn add(&mut self, value: u64) -> u64 {
-        let value = self.ctxt.current + value;
-        self.set(value, false)
+    /// Add current value of the progress bar.
+    pub fn add(&self, value: u64) -> Result<u64> {
+        let value = self.ctxt.current.fetch_add(value, Ordering::Relaxed) + value;
+        self.update(false)?;
+        Ok(value)
     }
 
-    /// Increase current value of the progress bar .
-    pub fn increase(&mut self) -> u64 {
+    /// Increase current value of the progress bar by one.
+    pub fn inc(&self) -> Result<u64> {
         self.add(1)
     }
 
+    /// Increase current value of the progress bar by one, same as `inc`.
+    pub fn increase(&self) -> Result<u64> {
+        self.inc()
+    }
+
     /// Finish progress.
-    pub fn finish(&mut self) {
-        self.ctxt.current = self.ctxt.total;
-        self.update(false);
+    pub fn finish(&self) -> Result<()> {
+        self.ctxt.finish();
+        let mut state = self.ctxt.state();
+        self.draw(&mut state, None, true)
     }
 
     /// Finish progress and write message 'msg' below the progress bar.
-    pub fn finish_with_msg(&mut self, msg: &str) {
-        self.ctxt.current = self.ctxt.total;
-        self.update(false);
-        let line = format!("\n{}", msg);
-        self.target
-            .handle_draw_info(ProgressBarDrawInfo { line, done: true })
-            .unwrap();
+    pub fn finish_with_msg(&self, msg: &str) -> Result<()> {
+        self.ctxt.finish();
+        let mut state = self.ctxt.state();
+        self.draw(&mut state, None, false)?;
+        self.draw(&mut state, Some(format!("\n{}", msg)), true)
     }
 
     /// Finish progress and replace the progress bar with message 'msg'.
-    pub fn finish_and_clear(&mut self, msg: &str) {
-        self.ctxt.current = self.ctxt.total;
-        self.update(false);
-        let msg_len = msg.len();
+    pub fn finish_and_clear(&self, msg: &str) -> Result<()> {
+        self.ctxt.finish();
+        let mut state = self.ctxt.state();
+        self.draw(&mut state, None, false)?;
         let line = format!(
             "\r{}{}",
             msg,
-            repeat(" ")
-                .take(self.ctxt.width - msg_len)
-                .collect::<String>()
+            " ".repeat(state.width.saturating_sub(display_width(msg)))
         );
-        self.target
-            .handle_draw_info(ProgressBarDrawInfo { line, done: true })
-            .unwrap();
+        self.draw(&mut state, Some(line), true)
+    }
+
+    /// Pause the progress, the paused time is excluded from the elapsed time,
+    /// and the speed and the time left are shown as paused.
+    pub fn pause(&self) -> Result<()> {
+        {
+            let _state = self.ctxt.state();
+            self.ctxt.pause();
+        }
+        self.update(true)
+    }
+
+    /// Resume the paused progress.
+    pub fn resume(&self) -> Result<()> {
+        {
+            let _state = self.ctxt.state();
+            self.ctxt.resume();
+        }
+        self.update(true)
+    }
+
+    /// Return whether the progress is paused.
+    pub fn is_paused(&self) -> bool {
+        self.ctxt.is_paused()
+    }
+
+    /// Print `msg` as a permanent line above the progress bar without corrupting it,
+    /// the line is printed below once the progress bar is finished,
+    /// and only the line is printed while the progress bar is suspended.
+    pub fn println(&self, msg: &str) -> Result<()> {
+        let mut state = self.ctxt.state();
+        if !state.target.is_interactive() || state.suspended > 0 {
+            let result = state.target.draw(&format!("{}\n", msg));
+            return state.error_policy.check(result);
+        }
+        let line = match state.done {
+            true => None,
+            false => Some(self.render_line(&mut state)),
+        };
+        let result = state.target.println(msg, line.as_deref());
+        state.error_policy.check(result)
+    }
+
+    /// Erase the progress bar, run `f` with a clean terminal, then redraw the progress bar.
+    ///
+    /// All progress bars are erased if the progress bar is attached to MultiProgressBar,
+    /// the progress bar isn't drawn while `f` is running even if it's updated.
+    /// `f` is run directly if MultiProgressBar isn't drawing in `join` at the moment,
+    /// but a channel progress bar blocks until its events are received.
+    ///
+    /// `f` is run even if the progress bar failed to be erased, and its result is always returned,
+    /// with the failures of erasing and redrawing checked by the error policy.
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> (R, Result<()>) {
+        let suspension = {
+            let mut state = self.ctxt.state();
+            state.suspended += 1;
+            if state.suspended == 1 && !state.done && state.target.is_interactive() {
+                state.target.suspend()
+            } else {
+                Ok(None)
+            }
+        };
+        // wait without the state lock, the other progress bars may update this one meanwhile.
+        let erased = suspension.and_then(|suspension| match suspension {
+            Some((ack, resume)) => {
+                ack.recv().map_err(|_| Error::Disconnected)?;
+                self.ctxt.state().resume = Some(resume);
+                Ok(())
+            }
+            None => Ok(()),
+        });
+
+        let result = f();
+
+        let mut state = self.ctxt.state();
+        state.suspended -= 1;
+        let policy = state.error_policy;
+        if state.suspended > 0 {
+            return (result, policy.check(erased));
+        }
+        state.resume = None;
+        let redrawn = self.draw(&mut state, None, false);
+        (result, policy.check(erased).and(redrawn))
+    }
+
+    /// Redraw the progress bar without changing the progress.
+    pub fn tick(&self) -> Result<()> {
+        self.update(true)
+    }
+
+    fn update(&self, is_force: bool) -> Result<()> {
+        if self.ctxt.claim_refresh(is_force) {
+            let mut state = self.ctxt.state();
+            self.draw(&mut state, None, false)?;
+        }
+        Ok(())
+    }
+
+    /// Send `line` (or the rendered progress bar if `None`) to the target,
+    /// nothing is drawn once the progress bar has been marked as done.
+    fn draw(&self, state: &mut ProgressBarState, line: Option<String>, done: bool) -> Result<()> {
+        if state.done || (state.suspended > 0 && !done) {
+            return Ok(());
+        }
+        state.done = done;
+        if done {
+            if let Some(ticker) = state.ticker.take() {
+                ticker.unpark();
+            }
+        }
+        let drawn = match state.target.non_interactive_mode(state.non_interactive) {
+            // the finishing message is still printed, as the lines of `println` are.
+            Some(NonInteractiveMode::Hidden) => match line.as_deref().map(str::trim) {
+                Some(msg) if done && !msg.is_empty() => state.target.draw(&format!("{}\n", msg)),
+                _ => Ok(()),
+            },
+            Some(NonInteractiveMode::Log(mode)) => self.draw_log(state, mode, line, done),
+            None => {
+                let line = line.unwrap_or_else(|| self.render_line(state));
+                let result = state
+                    .target
+                    .handle_draw_info(ProgressBarDrawInfo { line, done });
+                return state.error_policy.check(result);
+            }
+        };
+        // MultiProgressBar still counts the finished progress bars if nothing is drawn.
+        if drawn.is_ok() && state.target.is_channel() {
+            let line = String::new();
+            let result = state
+                .target
+                .handle_draw_info(ProgressBarDrawInfo { line, done });
+            return state.error_policy.check(result);
+        }
+        drawn
     }
 
-    fn update(&mut self, is_force: bool) {
-        let now = Instant::now();
-        let duration = now.duration_since(self.ctxt.last_refresh_time);
+    /// Print the progress as full lines on the non-interactive terminal,
+    /// the finishing line is followed by the message of `line` if any.
+    fn draw_log(
+        &self,
+        state: &mut ProgressBarState,
+        mode: LogMode,
+        line: Option<String>,
+        done: bool,
+    ) -> Result<()> {
+        let step = match mode.interval {
+            LogInterval::Time(_) => None,
+            LogInterval::Percent(n) => self
+                .ctxt
+                .percent()
+                .map(|percent| (percent * 100.0) as u64 / u64::from(n.max(1))),
+        };
+        let finished = self.ctxt.is_finish();
+        let due = match (mode.interval, &state.logged) {
+            (_, None) => true,
+            // the finished progress is printed only once.
+            (_, Some(logged)) if logged.finished && finished => false,
+            (LogInterval::Time(interval), Some(logged)) => logged.time.elapsed() >= interval,
+            (LogInterval::Percent(_), Some(logged)) => step != logged.step,
+        };
 
-        if is_force || self.ctxt.is_finish() || duration >= self.ctxt.refresh_rate {
-            self.ctxt.last_refresh_time = now;
+        let mut out = String::new();
+        if due || (done && !state.logged.as_ref().is_some_and(|logged| logged.finished)) {
+            state.logged = Some(LoggedLine {
+                time: Instant::now(),
+                step,
+                finished,
+            });
+            out += &format!("{}\n", self.render(state, mode.bar).trim());
+        }
+        if let Some(msg) = line.as_deref().map(str::trim).filter(|msg| !msg.is_empty()) {
+            out += &format!("{}\n", msg);
+        }
+        if out.is_empty() {
+            return Ok(());
+        }
+        let result = state.target.draw(&out);
+        state.error_policy.check(result)
+    }
 
-            let line = self.dispatch();
-            self.target
-                .handle_draw_info(ProgressBarDrawInfo { line, done: false })
-                .unwrap();
+    /// Render the progress bar cut to the width, so that it never wraps on the terminal.
+    fn render_line(&self, state: &mut ProgressBarState) -> String {
+        let line = self.render(state, true);
+        truncate_to_width(&line, state.width)
+    }
+
+    fn render(&self, state: &mut ProgressBarState, bar: bool) -> String {
+        if !self.ctxt.is_paused() {
+            let (current, _) = self.ctxt.current();
+            state.estimator.record(current, self.ctxt.time_elapsed());
+        }
+        state.ticks += 1;
+        // the terminal may have been resized since the last frame.
+        if !state.fixed_width {
+            state.width = state.target.terminal_width();
         }
+        self.dispatch(state, bar)
     }
 }
 
+/// The loop of the background thread spawned by `enable_steady_tick`,
+/// it only holds a weak reference so that dropping the progress bar stops it.
+fn steady_tick(ctxt: Weak<ProgressBarContext>, interval: Duration) {
+    loop {
+        thread::park_timeout(interval);
+
+        let pbar = match ctxt.upgrade() {
+            Some(ctxt) => ProgressBar { ctxt },
+            None => return,
+        };
+        {
+            let state = pbar.ctxt.state();
+            let is_current = state
+                .ticker
+                .as_ref()
+                .is_some_and(|ticker| ticker.id() == thread::current().id());
+            if state.done || !is_current {
+                return;
+            }
+        }
+        // the failures of drawing can't be returned from the background thread.
+        let _ = pbar.tick();
+    }
+}
+
+/// A component rendered by `dispatch`, the bars filling the width are rendered last.
+enum Part<'a> {
+    Text(String),
+    FillBar(&'a Component),
+}
+
+/// The text styles of the bar, the empty part has its own style.
+#[derive(Clone, Copy, Default)]
+struct BarPaint {
+    fill: TextStyle,
+    empty: TextStyle,
+}
+
 impl ProgressBar {
-    fn dispatch(&mut self) -> String {
-        let mut out = String::with_capacity(self.ctxt.width);
-        out += &self.fmt_title();
-
-        for component in &self.style.layout {
-            let s = match component {
-                Component::Counter(delimiter, fmt) => self.fmt_counter(delimiter, fmt),
-                Component::Percent => self.fmt_percent(),
-                Component::Bar(symbols, width) => self.fmt_bar(symbols, *width),
-                Component::TimeLeft(fmt) => self.fmt_time(self.ctxt.time_left(), fmt),
-                Component::TimeElapsed(fmt) => self.fmt_time(self.ctxt.time_elapsed(), fmt),
-                Component::TimeTotal(fmt) => self.fmt_time(self.ctxt.time_total(), fmt),
-                Component::Speed(fmt) => self.fmt_speed(self.ctxt.speed(), fmt),
-                Component::Str(s) => s.to_string(),
+    /// Render the components of the style, the bar component is skipped unless `bar`.
+    ///
+    /// The bars filling the width are rendered once the other components are measured,
+    /// sharing the columns left.
+    fn dispatch(&self, state: &ProgressBarState, bar: bool) -> String {
+        let mut out = String::with_capacity(state.width);
+        out += "\r";
+        let layout = &state.style.layout;
+        if !layout.iter().any(|c| matches!(c.base(), Component::Title)) {
+            out += &self.fmt_title(&state.title);
+        }
+        let speed = state.estimator.speed();
+
+        let mut parts = Vec::with_capacity(layout.len());
+        for component in layout {
+            let part = match component.base() {
+                Component::Bar(..) | Component::SmoothBar(_) if !bar => continue,
+                Component::Bar(_, BarWidth::Fill) | Component::SmoothBar(BarWidth::Fill) => {
+                    Part::FillBar(component)
+                }
+                _ => Part::Text(self.fmt_component(state, component, speed, None)),
+            };
+            parts.push(part);
+        }
+
+        let separator = &state.style.separator;
+        let mut fills = parts
+            .iter()
+            .filter(|part| matches!(part, Part::FillBar(_)))
+            .count();
+        let used = parts
+            .iter()
+            .fold(display_width(&out), |used, part| match part {
+                Part::Text(s) => used + display_width(s) + display_width(separator),
+                Part::FillBar(_) => used + display_width(separator),
+            });
+        let mut left = state.width.saturating_sub(used);
+        for part in parts {
+            let s = match part {
+                Part::Text(s) => s,
+                Part::FillBar(component) => {
+                    let share = left / fills;
+                    left -= share;
+                    fills -= 1;
+                    self.fmt_component(state, component, speed, Some(share))
+                }
             };
             out += &s;
-            out += " ";
+            out += separator;
         }
         out
     }
 
-    fn fmt_title(&self) -> String {
-        format!("\r{:<} ", self.ctxt.title)
+    /// Render a component with its text styles,
+    /// `share` is the columns taken by a bar filling the width.
+    fn fmt_component(
+        &self,
+        state: &ProgressBarState,
+        component: &Component,
+        speed: f64,
+        share: Option<usize>,
+    ) -> String {
+        let (component, style, empty) = component.unstyled();
+        let paint = match state.colors {
+            true => BarPaint {
+                fill: style,
+                empty: empty.unwrap_or(style),
+            },
+            false => BarPaint::default(),
+        };
+        let bar_width = |width: &BarWidth, ends: usize| match (width, share) {
+            (BarWidth::Fixed(width), _) => *width,
+            (BarWidth::Fill, share) => share.unwrap_or(0).saturating_sub(ends),
+        };
+
+        let s = match component {
+            // the bars are painted part by part.
+            Component::Bar(symbols, width) => {
+                let ends = display_width(&symbols.begin) + display_width(&symbols.end);
+                let width = bar_width(width, ends);
+                return self.fmt_bar(symbols, width, state.ticks, paint);
+            }
+            Component::SmoothBar(width) => {
+                let width = bar_width(width, 2);
+                return self.fmt_smooth_bar(width, state.unicode, state.ticks, paint);
+            }
+            Component::TimeLeft(_) | Component::Speed(_) if self.ctxt.is_paused() => {
+                self.fmt_paused()
+            }
+            Component::Counter(delimiter, fmt) => self.fmt_counter(delimiter, fmt),
+            Component::Percent => self.fmt_percent(),
+            Component::TimeLeft(fmt) => self.fmt_time(self.ctxt.time_left(speed), fmt),
+            Component::TimeElapsed(fmt) => self.fmt_time(Some(self.ctxt.time_elapsed()), fmt),
+            Component::TimeTotal(fmt) => self.fmt_time(self.ctxt.time_total(speed), fmt),
+            Component::Speed(fmt) => self.fmt_speed(speed, fmt),
+            Component::Str(s) => s.to_string(),
+            Component::Message => state.message.clone(),
+            Component::Title => state.title.clone(),
+            Component::Position(fmt) => FormattedUnit::new(fmt, self.position() as f64).to_string(),
+            Component::Total(fmt) => match self.total() {
+                Some(total) => FormattedUnit::new(fmt, total as f64).to_string(),
+                None => "?".to_string(),
+            },
+            Component::PercentValue => match self.ctxt.percent() {
+                Some(percent) => ((percent * 100f64) as u64).to_string(),
+                None => "?".to_string(),
+            },
+            Component::Spinner(spinner) => spinner
+                .frame(self.ctxt.time_elapsed(), self.ctxt.is_finish())
+                .to_string(),
+            Component::Custom(component) => component.render(&RenderContext {
+                position: self.position(),
+                total: self.total(),
+                elapsed: self.ctxt.time_elapsed(),
+                estimator: state.estimator.as_ref(),
+                message: &state.message,
+            }),
+            Component::Padded(component, alignment, width) => {
+                let s = self.fmt_component(state, component, speed, share);
+                pad_to_width(&s, *alignment, *width)
+            }
+            Component::Styled(..) => unreachable!("the styles have been unwrapped"),
+        };
+        paint.fill.paint(&s)
+    }
+
+    fn fmt_title(&self, title: &str) -> String {
+        format!("{:<} ", title)
+    }
+
+    fn fmt_paused(&self) -> String {
+        format!("{:<10}", "paused")
     }
 
     fn fmt_counter(&self, delimiter: &str, fmt: &UnitFormat) -> String {
-        let (current, total) = self.ctxt.current();
-        match fmt {
-            UnitFormat::Default => format!(
+        match self.ctxt.current() {
+            (current, Some(total)) => format!(
                 "{:>} {} {:<}",
-                FormattedUnit::Default(current as f64),
+                FormattedUnit::new(fmt, current as f64),
                 delimiter,
-                FormattedUnit::Default(total as f64)
-            ),
-            UnitFormat::Bytes => format!(
-                "{:>} {} {:<}",
-                FormattedUnit::Bytes(current as f64),
-                delimiter,
-                FormattedUnit::Bytes(total as f64)
-            ),
-            UnitFormat::BytesDec => format!(
-                "{:>} {} {:<}",
-                FormattedUnit::BytesDec(current as f64),
-                delimiter,
-                FormattedUnit::BytesDec(total as f64)
+                FormattedUnit::new(fmt, total as f64)
             ),
+            (current, None) => format!("{:>}", FormattedUnit::new(fmt, current as f64)),
         }
     }
 
-    fn fmt_bar(&self, symbols: &[char], bar_width: usize) -> String {
-        let percent = self.ctxt.percent();
-        let begin_part = symbols[0].to_string();
-        let fill_len = (percent * bar_width as f64) as usize;
-        let fill_part = repeat(symbols[1]).take(fill_len).collect::<String>();
-        let cur_part = symbols[2].to_string();
-        let empty_len = bar_width.saturating_sub(fill_len).saturating_sub(1);
-        let empty_part = repeat(symbols[3]).take(empty_len).collect::<String>();
-        let end_part = symbols[4].to_string();
+    fn fmt_bar(
+        &self,
+        symbols: &BarSymbols,
+        bar_width: usize,
+        ticks: u64,
+        paint: BarPaint,
+    ) -> String {
+        let percent = match self.ctxt.percent() {
+            Some(percent) => percent,
+            None => return self.fmt_bouncing_bar(symbols, bar_width, ticks, paint),
+        };
+        // the widths are counted in columns, wide symbols are repeated less.
+        let fill_width = display_width(&symbols.fill).max(1);
+        let fill_len = (percent * bar_width as f64) as usize / fill_width;
+        let fill_part = symbols.fill.repeat(fill_len);
+        let empty_width = bar_width
+            .saturating_sub(fill_len * fill_width)
+            .saturating_sub(display_width(&symbols.head));
+        let empty_part = repeat_to_width(&symbols.empty, empty_width);
 
         if !self.ctxt.is_finish() {
             format!(
-                "{}{}{}{}{}",
-                begin_part, fill_part, cur_part, empty_part, end_part
+                "{}{}{}",
+                paint
+                    .fill
+                    .paint(&format!("{}{}{}", symbols.begin, fill_part, symbols.head)),
+                paint.empty.paint(&empty_part),
+                paint.fill.paint(&symbols.end)
             )
         } else {
-            format!("{}{}{}", begin_part, fill_part, end_part)
+            let fill_part = repeat_to_width(&symbols.fill, bar_width);
+            paint
+                .fill
+                .paint(&format!("{}{}{}", symbols.begin, fill_part, symbols.end))
         }
     }
 
+    /// A bar filling the partial cell with eighth blocks, or with whole cells of '#'
+    /// if the locale is not UTF-8.
+    fn fmt_smooth_bar(
+        &self,
+        bar_width: usize,
+        unicode: bool,
+        ticks: u64,
+        paint: BarPaint,
+    ) -> String {
+        let (full, eighths): (char, &[char]) = match unicode {
+            true => ('█', &['▏', '▎', '▍', '▌', '▋', '▊', '▉']),
+            false => ('#', &[]),
+        };
+        let percent = match self.ctxt.percent() {
+            Some(percent) => percent,
+            None => {
+                let full = full.to_string();
+                let symbols = BarSymbols::new("|", &full, &full, " ", "|");
+                return self.fmt_bouncing_bar(&symbols, bar_width, ticks, paint);
+            }
+        };
+
+        let steps = (percent * (bar_width * 8) as f64) as usize;
+        let fill_len = (steps / 8).min(bar_width);
+        let mut fill_part = format!("|{}", full.to_string().repeat(fill_len));
+        let partial = match steps % 8 {
+            0 => None,
+            step => eighths.get(step - 1),
+        };
+        let empty_part = match (partial, fill_len < bar_width) {
+            (Some(partial), true) => {
+                fill_part.push(*partial);
+                " ".repeat(bar_width - fill_len - 1)
+            }
+            _ => " ".repeat(bar_width - fill_len),
+        };
+        format!(
+            "{}{}{}",
+            paint.fill.paint(&fill_part),
+            paint.empty.paint(&empty_part),
+            paint.fill.paint("|")
+        )
+    }
+
+    /// A segment bouncing between both ends of the bar, used when the total is unknown.
+    fn fmt_bouncing_bar(
+        &self,
+        symbols: &BarSymbols,
+        bar_width: usize,
+        ticks: u64,
+        paint: BarPaint,
+    ) -> String {
+        let segment_len = (bar_width / 4).max(1).min(bar_width);
+        let travel = (bar_width - segment_len) as u64;
+        let offset = match travel {
+            0 => 0,
+            _ => match ticks % (2 * travel) {
+                step if step <= travel => step,
+                step => 2 * travel - step,
+            },
+        } as usize;
+
+        format!(
+            "{}{}{}{}{}",
+            paint.fill.paint(&symbols.begin),
+            paint.empty.paint(&repeat_to_width(&symbols.empty, offset)),
+            paint
+                .fill
+                .paint(&repeat_to_width(&symbols.fill, segment_len)),
+            paint.empty.paint(&repeat_to_width(
+                &symbols.empty,
+                bar_width - segment_len - offset
+            )),
+            paint.fill.paint(&symbols.end)
+        )
+    }
+
     fn fmt_percent(&self) -> String {
-        format!("{:>3}%", (self.ctxt.percent() * 100f64) as u64)
+        match self.ctxt.percent() {
+            Some(percent) => format!("{:>3}%", (percent * 100f64) as u64),
+            None => "  ?%".to_string(),
+        }
     }
 
-    fn fmt_time(&self, time: Duration, fmt: &TimeFormat) -> String {
-        match fmt {
-            TimeFormat::Fmt1 => format!("{:<10}", FormattedTime::Fmt1(time)),
-            TimeFormat::Fmt2 => format!("{:<10}", FormattedTime::Fmt2(time)),
+    fn fmt_time(&self, time: Option<Duration>, fmt: &TimeFormat) -> String {
+        match (time, fmt) {
+            (Some(time), TimeFormat::Fmt1) => format!("{:<10}", FormattedTime::Fmt1(time)),
+            (Some(time), TimeFormat::Fmt2) => format!("{:<10}", FormattedTime::Fmt2(time)),
+            (None, TimeFormat::Fmt1) => format!("{:<10}", "--:--"),
+            (None, TimeFormat::Fmt2) => format!("{:<10}", "--s"),
         }
     }
 
     fn fmt_speed(&self, speed: f64, fmt: &UnitFormat) -> String {
         match fmt {
             UnitFormat::Default => format!("{:>8}it/s", FormattedUnit::Default(speed)),
-            UnitFormat::Bytes => format!("{:>8}/s", FormattedUnit::Default(speed)),
-            UnitFormat::BytesDec => format!("{:>8}/s", FormattedUnit::Default(speed)),
+            UnitFormat::Bytes => format!("{:>8}/s", FormattedUnit::Bytes(speed)),
+            UnitFormat::BytesDec => format!("{:>8}/s", FormattedUnit::BytesDec(speed)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spinner::Spinner;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+    fn is_done(event: &ProgressBarEvent) -> bool {
+        matches!(event, ProgressBarEvent::Draw(_, info) if info.done)
+    }
+
+    fn recv_line(rx: &mpsc::Receiver<ProgressBarEvent>) -> String {
+        match rx.recv().unwrap() {
+            ProgressBarEvent::Draw(_, info) => info.line,
+            ProgressBarEvent::Println(msg) => msg,
+            ProgressBarEvent::Suspend(..) => "suspend".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_shared_progress_bar() {
+        assert_send_sync::<ProgressBar>();
+
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(1000), 0, tx);
+        let handles = (0..4)
+            .map(|_| {
+                let pbar = pbar.clone();
+                thread::spawn(move || {
+                    for _ in 0..250 {
+                        pbar.inc().unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(pbar.position(), 1000);
+
+        pbar.finish().unwrap();
+        assert_eq!(rx.try_iter().filter(is_done).count(), 1);
+    }
+
+    #[test]
+    fn test_indeterminate_progress_bar() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(None, 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style
+            .counter(None, None)
+            .percent()
+            .bar("[#>-]", Some(8))
+            .unwrap();
+        pbar.set_style(style).unwrap();
+
+        let lines = (0..3)
+            .map(|_| {
+                pbar.set(42, true).unwrap();
+                recv_line(&rx)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines[0], "\r 42   ?% [-##-----] ");
+        assert_eq!(lines[1], "\r 42   ?% [--##----] ");
+        assert_eq!(lines[2], "\r 42   ?% [---##---] ");
+
+        pbar.finish().unwrap();
+        assert_eq!(recv_line(&rx), "\r 42 100% [########] ");
+    }
+
+    #[test]
+    fn test_max_total() {
+        let (tx, _rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(u64::MAX), 0, tx);
+        assert_eq!(pbar.total(), Some(u64::MAX - 1));
+        pbar.set_total(u64::MAX).unwrap();
+        assert_eq!(pbar.total(), Some(u64::MAX - 1));
+        pbar.inc_total(1).unwrap();
+        assert_eq!(pbar.total(), Some(u64::MAX - 1));
+    }
+
+    #[test]
+    fn test_time_left_overflow() {
+        struct Stalled;
+        impl Estimator for Stalled {
+            fn record(&mut self, _: u64, _: Duration) {}
+            fn speed(&self) -> f64 {
+                1e-300
+            }
+            fn reset(&mut self) {}
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        assert_eq!(pbar.ctxt.time_left(1e-300), None);
+        assert_eq!(pbar.ctxt.time_total(1e-300), None);
+        assert_eq!(
+            pbar.ctxt.time_total(1.0),
+            Some(pbar.ctxt.time_elapsed() + Duration::from_secs(10))
+        );
+
+        let mut style = ProgressBarStyle::customizable();
+        style.time_left(None).time_total(None);
+        pbar.set_style(style).unwrap().set_estimator(Stalled);
+        pbar.set(5, true).unwrap();
+        assert_eq!(recv_line(&rx), "\r --:--      --:--      ");
+    }
+
+    #[test]
+    fn test_steady_tick() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(None, 0, tx);
+        pbar.enable_steady_tick(Duration::from_millis(1));
+        for _ in 0..3 {
+            assert!(!is_done(&rx.recv().unwrap()));
+        }
+        assert_eq!(pbar.position(), 0);
+
+        pbar.finish().unwrap();
+        assert!(is_done(&rx.try_iter().last().unwrap()));
+
+        let (tx, _rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(None, 0, tx);
+        pbar.enable_steady_tick(Duration::ZERO);
+        assert_eq!(pbar.ctxt.state().tick_interval, Some(MIN_TICK_INTERVAL));
+        pbar.disable_steady_tick();
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let (tx, _rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(100), 0, tx);
+
+        pbar.pause().unwrap();
+        assert!(pbar.is_paused());
+        thread::sleep(Duration::from_millis(50));
+        pbar.tick().unwrap();
+        assert!(pbar.ctxt.time_elapsed() < Duration::from_millis(50));
+
+        pbar.resume().unwrap();
+        assert!(!pbar.is_paused());
+        pbar.tick().unwrap();
+        assert!(pbar.ctxt.time_elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_dynamic_total_and_reset() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(None, 0, tx);
+        pbar.inc_total(10).unwrap();
+        assert_eq!(pbar.total(), Some(10));
+        pbar.inc_total(10).unwrap();
+        pbar.add(15).unwrap();
+        assert!(!pbar.ctxt.is_finish());
+        pbar.set_total(15).unwrap();
+        assert!(pbar.ctxt.is_finish());
+
+        pbar.finish().unwrap();
+        pbar.reset().unwrap();
+        assert_eq!(pbar.position(), 0);
+        assert_eq!(pbar.total(), Some(15));
+        assert!(!pbar.ctxt.is_finish());
+        assert!(!is_done(&rx.try_iter().last().unwrap()));
+    }
+
+    #[test]
+    fn test_message() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style.counter(None, None).message();
+        pbar.set_style(style).unwrap();
+
+        pbar.set_refresh_rate(Duration::new(0, 0));
+        pbar.set_message("stage 1").unwrap();
+        assert_eq!(recv_line(&rx), "\r 0 / 10 stage 1 ");
+    }
+
+    #[test]
+    fn test_log_line_without_bar() {
+        let (tx, _rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style
+            .counter(None, None)
+            .bar("[#>-]", Some(4))
+            .unwrap()
+            .percent();
+        pbar.set_style(style).unwrap();
+        pbar.set_title("Simple:");
+        pbar.ctxt.current.store(5, Ordering::Relaxed);
+
+        let state = pbar.ctxt.state();
+        assert_eq!(pbar.dispatch(&state, false).trim(), "Simple: 5 / 10  50%");
+        assert_eq!(
+            pbar.dispatch(&state, true).trim(),
+            "Simple: 5 / 10 [##>-]  50%"
+        );
+    }
+
+    #[test]
+    fn test_attached_capabilities() {
+        let (tx, _rx) = mpsc::channel();
+        let log = ProgressBarTarget::log_stdout(LogMode::new(LogInterval::Percent(10)));
+        let multi = Arc::new(MultiTarget::new(ProgressBarTarget::log_stdout(
+            LogMode::new(LogInterval::Percent(10)),
+        )));
+        let target = ProgressBarTarget::attached(0, tx, multi);
+        assert!(!target.supports_color());
+        assert_eq!(target.terminal_width(), log.terminal_width());
+        assert!(
+            !ProgressBar::with_target(target, Some(10))
+                .ctxt
+                .state
+                .lock()
+                .unwrap()
+                .colors
+        );
+    }
+
+    #[test]
+    fn test_hidden_finish_msg() {
+        let events = |finish: fn(&ProgressBar) -> Result<()>| {
+            let (tx, rx) = mpsc::channel();
+            let mode = LogMode::new(LogInterval::Percent(10));
+            let multi = Arc::new(MultiTarget::new(ProgressBarTarget::log_stdout(mode)));
+            let pbar =
+                ProgressBar::with_target(ProgressBarTarget::attached(0, tx, multi), Some(10));
+            pbar.set(5, true).unwrap();
+            finish(&pbar).unwrap();
+            rx.try_iter()
+                .map(|event| match event {
+                    ProgressBarEvent::Println(line) => line,
+                    ProgressBarEvent::Draw(_, info) => format!("{:?} {}", info.line, info.done),
+                    ProgressBarEvent::Suspend(..) => "suspend".to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            events(|pbar| pbar.finish_with_msg("Done...")),
+            vec!["\"\" false", "\"\" false", "Done...", "\"\" true"]
+        );
+        assert_eq!(
+            events(|pbar| pbar.finish_and_clear("Cleared")),
+            vec!["\"\" false", "\"\" false", "Cleared", "\"\" true"]
+        );
+        assert_eq!(
+            events(|pbar| pbar.finish()),
+            vec!["\"\" false", "\"\" true"]
+        );
+    }
+
+    #[test]
+    fn test_attached_log_mode() {
+        let (tx, rx) = mpsc::channel();
+        let mode = LogMode::new(LogInterval::Percent(50));
+        let multi = Arc::new(MultiTarget::new(ProgressBarTarget::log_stdout(mode)));
+        *multi.non_interactive.lock().unwrap() = NonInteractiveMode::Log(mode);
+        let pbar = ProgressBar::with_target(ProgressBarTarget::attached(0, tx, multi), Some(10));
+        let mut style = ProgressBarStyle::customizable();
+        style.counter(None, None).bar("[#>-]", Some(4)).unwrap();
+        pbar.set_style(style).unwrap().set_title("Log:");
+
+        for i in 1..=9 {
+            pbar.set(i, true).unwrap();
+        }
+        pbar.finish().unwrap();
+        let lines = rx
+            .try_iter()
+            .filter_map(|event| match event {
+                ProgressBarEvent::Println(line) => Some(line),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec!["Log: 1 / 10", "Log: 5 / 10", "Log: 10 / 10"]);
+    }
+
+    #[test]
+    fn test_wide_symbols() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style.bar("[进>-]", Some(8)).unwrap();
+        pbar.set_style(style).unwrap();
+        pbar.set_title("进度:");
+
+        pbar.set(5, true).unwrap();
+        assert_eq!(recv_line(&rx), "\r进度: [进进>---] ");
+        pbar.finish().unwrap();
+        assert_eq!(recv_line(&rx), "\r进度: [进进进进] ");
+
+        pbar.set_width(8);
+        let mut state = pbar.ctxt.state();
+        state.done = false;
+        let line = pbar.render_line(&mut state);
+        assert_eq!(line, "\r进度: [");
+        assert_eq!(display_width(&line), 7);
+    }
+
+    #[test]
+    fn test_fill_bar() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style.counter(None, None).fill_bar("[#>-]").unwrap();
+        pbar.set_style(style).unwrap().set_title("t").set_width(30);
+
+        pbar.set(5, true).unwrap();
+        let line = recv_line(&rx);
+        assert_eq!(line, "\rt 5 / 10 [#########>--------] ");
+        assert_eq!(display_width(&line), 30);
+
+        pbar.set_width(24);
+        pbar.tick().unwrap();
+        assert_eq!(recv_line(&rx), "\rt 5 / 10 [######>-----] ");
+    }
+
+    #[test]
+    fn test_smooth_bar() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style.smooth_bar(Some(3));
+        pbar.set_style(style).unwrap();
+
+        pbar.ctxt.state().unicode = true;
+        pbar.set(5, true).unwrap();
+        assert_eq!(recv_line(&rx), "\r |█▌ | ");
+        pbar.ctxt.state().unicode = false;
+        pbar.tick().unwrap();
+        assert_eq!(recv_line(&rx), "\r |#  | ");
+        pbar.finish().unwrap();
+        assert_eq!(recv_line(&rx), "\r |###| ");
+    }
+
+    #[test]
+    fn test_styled_components() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style
+            .bar("[#>-]", Some(4))
+            .unwrap()
+            .styled(TextStyle::new().fg(Color::Green))
+            .empty_styled(TextStyle::new().dim())
+            .percent()
+            .styled(TextStyle::new().fg(Color::Cyan).bold());
+        pbar.set_style(style).unwrap();
+
+        pbar.ctxt.state().colors = true;
+        pbar.set(5, true).unwrap();
+        let line = recv_line(&rx);
+        assert_eq!(
+            line,
+            "\r \x1b[32m[##>\x1b[0m\x1b[2m-\x1b[0m\x1b[32m]\x1b[0m \x1b[1;36m 50%\x1b[0m "
+        );
+        assert_eq!(display_width(&line), 13);
+
+        pbar.ctxt.state().colors = false;
+        pbar.tick().unwrap();
+        assert_eq!(recv_line(&rx), "\r [##>-]  50% ");
+    }
+
+    #[test]
+    fn test_template() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let style =
+            ProgressBarStyle::from_template("{title} {bar:4} {pos:>3}/{len} {percent}%").unwrap();
+        pbar.set_style(style).unwrap().set_title("Simple:");
+
+        pbar.set(5, true).unwrap();
+        assert_eq!(recv_line(&rx), "\rSimple: [##>-]   5/10 50%");
+    }
+
+    #[test]
+    fn test_custom_component() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style
+            .counter(None, None)
+            .custom(|ctxt: &RenderContext<'_>| {
+                format!(
+                    "left={} msg={}",
+                    ctxt.total().unwrap() - ctxt.position(),
+                    ctxt.message()
+                )
+            });
+        pbar.set_style(style).unwrap().set_title("Custom:");
+        pbar.set_message("ok").unwrap();
+
+        pbar.set(3, true).unwrap();
+        assert_eq!(recv_line(&rx), "\rCustom: 3 / 10 left=7 msg=ok ");
+    }
+
+    #[test]
+    fn test_spinner() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(None, 0, tx);
+        let spinner = Spinner::new(&["a", "b"], Duration::from_secs(3600)).done("ok");
+        let mut style = ProgressBarStyle::customizable();
+        style.spinner(Some(spinner)).counter(None, None);
+        pbar.set_style(style).unwrap().set_title("Spin:");
+
+        pbar.set(5, true).unwrap();
+        assert_eq!(recv_line(&rx), "\rSpin: a 5 ");
+        pbar.finish().unwrap();
+        assert_eq!(recv_line(&rx), "\rSpin: ok 5 ");
+
+        let mut style = ProgressBarStyle::customizable();
+        style.spinner(Some(Spinner::new(&[], Duration::from_secs(1))));
+        assert!(matches!(pbar.set_style(style), Err(Error::InvalidStyle(_))));
+    }
+
+    #[test]
+    fn test_bar_symbols() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        style
+            .bar_symbols(BarSymbols::new("<<", "=", "", "", ">>"), BarWidth::Fixed(8))
+            .unwrap()
+            .bar_symbols(BarSymbols::new("", "-=", "|", ".", ""), BarWidth::Fixed(8))
+            .unwrap();
+        pbar.set_style(style).unwrap().set_title("Symbols:");
+
+        pbar.set(5, true).unwrap();
+        assert_eq!(recv_line(&rx), "\rSymbols: <<====    >> -=-=|... ");
+    }
+
+    #[test]
+    fn test_presets() {
+        struct Steady;
+        impl Estimator for Steady {
+            fn record(&mut self, _: u64, _: Duration) {}
+            fn speed(&self) -> f64 {
+                20.0
+            }
+            fn reset(&mut self) {}
+        }
+
+        let expected = [
+            (
+                "ascii",
+                "\rBuilding 42 / 100  42% [############>-----------------] 00:02 ",
+            ),
+            (
+                "blocks",
+                "\rBuilding  42% ▕████████████░░░░░░░░░░░░░░░░░░▏ 42 / 100 00:02 ",
+            ),
+            (
+                "pip",
+                "\rBuilding ━━━━━━━━━━━━━━━━╸━━━━━━━━━━━━━━━━━━━━━━━ 42B / 100B 20B/s eta 00:02 ",
+            ),
+            (
+                "cargo",
+                "\r    Building [==========>              ] 42/100: pbar",
+            ),
+            (
+                "dots",
+                "\rBuilding ⢸⣿⣿⣿⣿⣿⣿⣿⣿⣿⣿⣿⣿⣷⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⡇  42% 00:02 ",
+            ),
+            ("minimal", "\rBuilding |████████▍           |  42% "),
+            (
+                "bytes_transfer",
+                "\rBuilding 42B / 100B 20B/s  42% [############>-----------------] 00:02 ",
+            ),
+        ];
+        assert_eq!(ProgressBarStyle::presets(), expected.map(|(name, _)| name));
+        for (name, line) in expected {
+            let (tx, rx) = mpsc::channel();
+            let pbar = ProgressBar::channel(Some(100), 0, tx);
+            pbar.set_style(ProgressBarStyle::preset(name).unwrap())
+                .unwrap()
+                .set_title("Building")
+                .set_width(80)
+                .set_estimator(Steady);
+            let mut state = pbar.ctxt.state();
+            state.colors = false;
+            state.unicode = true;
+            drop(state);
+            pbar.set_message("pbar").unwrap();
+            pbar.set(42, true).unwrap();
+            let drawn = recv_line(&rx);
+            assert_eq!(drawn, line, "{}", name);
+            assert!(display_width(&drawn) <= 80, "{}", name);
+        }
+        assert!(ProgressBarStyle::preset("fancy").is_none());
+    }
+
+    #[test]
+    fn test_invalid_style() {
+        let (tx, _rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let mut style = ProgressBarStyle::customizable();
+        assert!(matches!(
+            style.bar("[#]", None),
+            Err(Error::InvalidStyle(_))
+        ));
+        assert!(matches!(
+            style.bar_symbols(BarSymbols::new("[", "", ">", "-", "]"), BarWidth::Fill),
+            Err(Error::InvalidStyle(_))
+        ));
+        style.layout.push(Component::Bar(
+            BarSymbols::new("", "", "", "", ""),
+            BarWidth::Fixed(4),
+        ));
+        assert!(matches!(pbar.set_style(style), Err(Error::InvalidStyle(_))));
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let (tx, rx) = mpsc::channel();
+        drop(rx);
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        assert!(matches!(pbar.tick(), Err(Error::Disconnected)));
+        pbar.set_error_policy(ErrorPolicy::Ignore);
+        assert_eq!(pbar.set(5, true).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_println() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        pbar.println("log line").unwrap();
+        assert_eq!(recv_line(&rx), "log line");
+    }
+
+    #[test]
+    fn test_println_while_suspended() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let renders = Arc::new(AtomicU64::new(0));
+        let mut style = ProgressBarStyle::customizable();
+        let counter = renders.clone();
+        style.custom(move |_: &RenderContext<'_>| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            String::new()
+        });
+        pbar.set_style(style).unwrap();
+        let listener = thread::spawn(move || match rx.recv().unwrap() {
+            ProgressBarEvent::Suspend(ack, resume) => {
+                ack.send(()).unwrap();
+                let printed = recv_line(&rx);
+                assert!(resume.recv().is_err());
+                (printed, recv_line(&rx))
+            }
+            _ => panic!("expected suspend event"),
+        });
+
+        let (printed, redrawn) = pbar.suspend(|| {
+            let printed = pbar.println("hello");
+            assert_eq!(renders.load(Ordering::SeqCst), 0);
+            printed
+        });
+        printed.unwrap();
+        redrawn.unwrap();
+        assert_eq!(
+            listener.join().unwrap(),
+            ("hello".to_string(), "\r  ".to_string())
+        );
+        assert_eq!(renders.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_suspend_failure() {
+        let (tx, rx) = mpsc::channel();
+        drop(rx);
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        let (result, redrawn) = pbar.suspend(|| 42);
+        assert_eq!(result, 42);
+        assert!(matches!(redrawn, Err(Error::Disconnected)));
+    }
+
+    #[test]
+    fn test_suspend() {
+        let (tx, rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(10), 0, tx);
+        pbar.set_width(80);
+        let listener = thread::spawn(move || match rx.recv().unwrap() {
+            ProgressBarEvent::Suspend(ack, resume) => {
+                ack.send(()).unwrap();
+                assert!(resume.recv().is_err());
+                recv_line(&rx)
+            }
+            _ => panic!("expected suspend event"),
+        });
+
+        let (result, redrawn) = pbar.suspend(|| {
+            pbar.set(5, true).unwrap();
+            42
+        });
+        assert_eq!(result, 42);
+        redrawn.unwrap();
+        assert_eq!(
+            listener.join().unwrap(),
+            "\r 5 / 10  50% [###############>--------------] 00:00 "
+        );
+    }
+}
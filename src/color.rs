@@ -0,0 +1,155 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The color of the text or the background, drawn with ANSI escape sequences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 colors.
+    Fixed(u8),
+    /// A true color.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Return the parameters of the SGR sequence, `base` is 30 for the text and 40 for the background.
+    fn sgr(self, base: u8) -> String {
+        let basic = |n: u8| (base + n).to_string();
+        let bright = |n: u8| (base + 60 + n).to_string();
+        match self {
+            Color::Black => basic(0),
+            Color::Red => basic(1),
+            Color::Green => basic(2),
+            Color::Yellow => basic(3),
+            Color::Blue => basic(4),
+            Color::Magenta => basic(5),
+            Color::Cyan => basic(6),
+            Color::White => basic(7),
+            Color::BrightBlack => bright(0),
+            Color::BrightRed => bright(1),
+            Color::BrightGreen => bright(2),
+            Color::BrightYellow => bright(3),
+            Color::BrightBlue => bright(4),
+            Color::BrightMagenta => bright(5),
+            Color::BrightCyan => bright(6),
+            Color::BrightWhite => bright(7),
+            Color::Fixed(n) => format!("{};5;{}", base + 8, n),
+            Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+        }
+    }
+}
+
+/// The colors and the attributes of a component, like `TextStyle::new().fg(Color::Green).bold()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct TextStyle {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+}
+
+impl TextStyle {
+    /// Return a plain text style.
+    pub fn new() -> TextStyle {
+        TextStyle::default()
+    }
+
+    /// Set the color of the text.
+    pub fn fg(mut self, color: Color) -> TextStyle {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Set the color of the background.
+    pub fn bg(mut self, color: Color) -> TextStyle {
+        self.bg = Some(color);
+        self
+    }
+
+    pub fn bold(mut self) -> TextStyle {
+        self.bold = true;
+        self
+    }
+
+    pub fn dim(mut self) -> TextStyle {
+        self.dim = true;
+        self
+    }
+
+    pub fn italic(mut self) -> TextStyle {
+        self.italic = true;
+        self
+    }
+
+    /// Wrap `s` with the escape sequences of the style, `s` is unchanged for a plain style.
+    pub(crate) fn paint(&self, s: &str) -> String {
+        let mut params = vec![];
+        if self.bold {
+            params.push("1".to_string());
+        }
+        if self.dim {
+            params.push("2".to_string());
+        }
+        if self.italic {
+            params.push("3".to_string());
+        }
+        if let Some(fg) = self.fg {
+            params.push(fg.sgr(30));
+        }
+        if let Some(bg) = self.bg {
+            params.push(bg.sgr(40));
+        }
+
+        match params.is_empty() || s.is_empty() {
+            true => s.to_string(),
+            false => format!("\x1b[{}m{}\x1b[0m", params.join(";"), s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(TextStyle::new().paint("eta"), "eta");
+        assert_eq!(
+            TextStyle::new().fg(Color::Cyan).paint("eta"),
+            "\x1b[36meta\x1b[0m"
+        );
+        assert_eq!(
+            TextStyle::new()
+                .bold()
+                .dim()
+                .fg(Color::Fixed(208))
+                .bg(Color::Rgb(1, 2, 3))
+                .paint("eta"),
+            "\x1b[1;2;38;5;208;48;2;1;2;3meta\x1b[0m"
+        );
+        assert_eq!(
+            TextStyle::new().bg(Color::BrightWhite).paint("eta"),
+            "\x1b[107meta\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+/// The error type of drawing progress bars.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to write to the terminal, like a closed stdout pipe.
+    Io(io::Error),
+    /// The MultiProgressBar (or the receiver of a channel target) is gone.
+    Disconnected,
+    /// The progress bar style is invalid, with the reason.
+    InvalidStyle(String),
+    /// The template of the progress bar style can't be parsed, with the reason.
+    InvalidTemplate(String),
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "failed to draw progress bar: {}", err),
+            Error::Disconnected => write!(f, "progress bar channel is disconnected"),
+            Error::InvalidStyle(reason) => write!(f, "invalid progress bar style: {}", reason),
+            Error::InvalidTemplate(reason) => {
+                write!(f, "invalid progress bar template: {}", reason)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        match err {
+            Error::Io(err) => err,
+            err => io::Error::other(err),
+        }
+    }
+}
+
+/// How to handle the failures of drawing progress bars.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Ignore the failures, the progress is still updated.
+    Ignore,
+    /// Return the failures to the caller, the default policy.
+    #[default]
+    Surface,
+}
+
+impl ErrorPolicy {
+    pub(crate) fn check<T: Default>(self, result: Result<T>) -> Result<T> {
+        match self {
+            ErrorPolicy::Ignore => Ok(result.unwrap_or_default()),
+            ErrorPolicy::Surface => result,
+        }
+    }
+}
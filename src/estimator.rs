@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::util::*;
+
+/// Estimate the speed of the progress from recorded samples,
+/// 'Speed','TimeLeft' and 'TimeTotal' components are all based on it.
+pub trait Estimator: Send {
+    /// Record a sample that the progress reached `position` after `elapsed` time.
+    fn record(&mut self, position: u64, elapsed: Duration);
+
+    /// Return the estimated speed, in steps per second.
+    fn speed(&self) -> f64;
+
+    /// Forget all recorded samples.
+    fn reset(&mut self);
+}
+
+/// Average speed since the start of the progress, the default estimator.
+#[derive(Clone, Default)]
+pub struct AverageEstimator {
+    last: Option<(u64, Duration)>,
+}
+
+impl AverageEstimator {
+    pub fn new() -> AverageEstimator {
+        AverageEstimator::default()
+    }
+}
+
+impl Estimator for AverageEstimator {
+    fn record(&mut self, position: u64, elapsed: Duration) {
+        self.last = Some((position, elapsed));
+    }
+
+    fn speed(&self) -> f64 {
+        match self.last {
+            Some((position, elapsed)) if elapsed > Duration::new(0, 0) => {
+                position as f64 / duration_to_secs(elapsed)
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Exponentially weighted moving average of the speed,
+/// a sample loses half of its weight after every `half_life`.
+#[derive(Clone)]
+pub struct EwmaEstimator {
+    half_life: Duration,
+    last: Option<(u64, Duration)>,
+    speed: f64,
+}
+
+impl EwmaEstimator {
+    pub fn new(half_life: Duration) -> EwmaEstimator {
+        EwmaEstimator {
+            half_life,
+            last: None,
+            speed: 0.0,
+        }
+    }
+}
+
+impl Default for EwmaEstimator {
+    /// Return an estimator with 5s half life.
+    fn default() -> EwmaEstimator {
+        EwmaEstimator::new(Duration::from_secs(5))
+    }
+}
+
+impl Estimator for EwmaEstimator {
+    fn record(&mut self, position: u64, elapsed: Duration) {
+        match self.last {
+            // the first sample is weighted as the average since the start.
+            None => {
+                if elapsed > Duration::new(0, 0) {
+                    self.speed = position as f64 / duration_to_secs(elapsed);
+                }
+            }
+            Some((last_position, last_elapsed)) => {
+                if elapsed <= last_elapsed {
+                    return;
+                }
+                let dt = duration_to_secs(elapsed - last_elapsed);
+                let rate = (position as f64 - last_position as f64) / dt;
+                let weight = match duration_to_secs(self.half_life) {
+                    half_life if half_life > 0.0 => 1.0 - 0.5f64.powf(dt / half_life),
+                    _ => 1.0,
+                };
+                self.speed += weight * (rate - self.speed);
+            }
+        }
+        self.last = Some((position, elapsed));
+    }
+
+    fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+        self.speed = 0.0;
+    }
+}
+
+/// Average speed within a sliding time window.
+#[derive(Clone)]
+pub struct WindowEstimator {
+    window: Duration,
+    samples: VecDeque<(u64, Duration)>,
+}
+
+impl WindowEstimator {
+    pub fn new(window: Duration) -> WindowEstimator {
+        WindowEstimator {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+}
+
+impl Default for WindowEstimator {
+    /// Return an estimator with 10s window.
+    fn default() -> WindowEstimator {
+        WindowEstimator::new(Duration::from_secs(10))
+    }
+}
+
+impl Estimator for WindowEstimator {
+    fn record(&mut self, position: u64, elapsed: Duration) {
+        self.samples.push_back((position, elapsed));
+        // keep the newest sample which is out of the window as the window start.
+        while self.samples.len() > 2 && elapsed.saturating_sub(self.samples[1].1) >= self.window {
+            self.samples.pop_front();
+        }
+    }
+
+    fn speed(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(&(first_position, first_elapsed)), Some(&(last_position, last_elapsed)))
+                if last_elapsed > first_elapsed =>
+            {
+                (last_position as f64 - first_position as f64)
+                    / duration_to_secs(last_elapsed - first_elapsed)
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Speed from the linear regression (least squares) of the latest samples.
+#[derive(Clone)]
+pub struct RegressionEstimator {
+    capacity: usize,
+    samples: VecDeque<(u64, Duration)>,
+}
+
+impl RegressionEstimator {
+    /// `capacity` is the number of latest samples used by the regression.
+    pub fn new(capacity: usize) -> RegressionEstimator {
+        RegressionEstimator {
+            capacity: capacity.max(2),
+            samples: VecDeque::new(),
+        }
+    }
+}
+
+impl Default for RegressionEstimator {
+    /// Return an estimator using the latest 20 samples.
+    fn default() -> RegressionEstimator {
+        RegressionEstimator::new(20)
+    }
+}
+
+impl Estimator for RegressionEstimator {
+    fn record(&mut self, position: u64, elapsed: Duration) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((position, elapsed));
+    }
+
+    fn speed(&self) -> f64 {
+        let n = self.samples.len() as f64;
+        if n < 2.0 {
+            return 0.0;
+        }
+
+        let points = self
+            .samples
+            .iter()
+            .map(|&(position, elapsed)| (duration_to_secs(elapsed), position as f64));
+        let (mean_x, mean_y) = points
+            .clone()
+            .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x / n, sy + y / n));
+        let (cov, var) = points.fold((0.0, 0.0), |(cov, var), (x, y)| {
+            (
+                cov + (x - mean_x) * (y - mean_y),
+                var + (x - mean_x) * (x - mean_x),
+            )
+        });
+
+        if var > 0.0 {
+            cov / var
+        } else {
+            0.0
+        }
+    }
+
+    fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_all(estimator: &mut dyn Estimator, samples: &[(u64, u64)]) {
+        for &(position, secs) in samples {
+            estimator.record(position, Duration::from_secs(secs));
+        }
+    }
+
+    // a slow start followed by a steady speed of 100 steps per second.
+    const SAMPLES: [(u64, u64); 6] = [
+        (0, 0),
+        (10, 10),
+        (1010, 20),
+        (2010, 30),
+        (3010, 40),
+        (4010, 50),
+    ];
+
+    #[test]
+    fn test_average_estimator() {
+        let mut estimator = AverageEstimator::new();
+        assert_eq!(estimator.speed(), 0.0);
+        record_all(&mut estimator, &SAMPLES);
+        assert!((estimator.speed() - 80.2).abs() < 1e-9);
+        estimator.reset();
+        assert_eq!(estimator.speed(), 0.0);
+    }
+
+    #[test]
+    fn test_ewma_estimator() {
+        let mut estimator = EwmaEstimator::new(Duration::from_secs(5));
+        record_all(&mut estimator, &SAMPLES);
+        assert!((estimator.speed() - 100.0).abs() < 100.0 / 16.0);
+    }
+
+    #[test]
+    fn test_window_estimator() {
+        let mut estimator = WindowEstimator::new(Duration::from_secs(20));
+        record_all(&mut estimator, &SAMPLES);
+        assert!((estimator.speed() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_regression_estimator() {
+        let mut estimator = RegressionEstimator::new(4);
+        record_all(&mut estimator, &SAMPLES);
+        assert!((estimator.speed() - 100.0).abs() < 1e-9);
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt;
 use std::time::Duration;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::util::*;
 
 const KIB: f64 = 1024.;
@@ -14,6 +17,8 @@ const GB: f64 = 1e9;
 const TB: f64 = 1e12;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum UnitFormat {
     /// format pure number.
     /// example: 123456
@@ -32,6 +37,16 @@ pub enum FormattedUnit {
     BytesDec(f64),
 }
 
+impl FormattedUnit {
+    pub fn new(fmt: &UnitFormat, unit: f64) -> FormattedUnit {
+        match fmt {
+            UnitFormat::Default => FormattedUnit::Default(unit),
+            UnitFormat::Bytes => FormattedUnit::Bytes(unit),
+            UnitFormat::BytesDec => FormattedUnit::BytesDec(unit),
+        }
+    }
+}
+
 impl fmt::Display for FormattedUnit {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -57,6 +72,8 @@ impl fmt::Display for FormattedUnit {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TimeFormat {
     /// format: MM:SS | HH:MM:SS | XX..Xd:HH:MM::SS
     /// example: 00:01; 59:59; 01:00:01; 1234d:23:05:10 ...
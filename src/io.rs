@@ -0,0 +1,152 @@
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+
+use crate::bar::*;
+
+/// A reader wrapper which drives a progress bar by the number of bytes read,
+/// seeking the reader sets the progress to the new position.
+pub struct ProgressBarReader<R> {
+    inner: R,
+    pbar: ProgressBar,
+}
+
+impl<R> ProgressBarReader<R> {
+    pub(crate) fn new(inner: R, pbar: ProgressBar) -> ProgressBarReader<R> {
+        ProgressBarReader { inner, pbar }
+    }
+
+    /// Return the progress bar driven by the reader.
+    pub fn progress_bar(&self) -> &ProgressBar {
+        &self.pbar
+    }
+
+    /// Unwrap the reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ProgressBarReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        // the bytes have been read, a failure of drawing mustn't lose them.
+        let _ = self.pbar.add(n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for ProgressBarReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        // the failures of drawing can't be returned from `consume`.
+        let _ = self.pbar.add(amt as u64);
+    }
+}
+
+impl<R: Seek> Seek for ProgressBarReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = self.inner.seek(pos)?;
+        // the position has been moved, a failure of drawing doesn't change it.
+        let _ = self.pbar.set(pos, false);
+        Ok(pos)
+    }
+}
+
+/// A writer wrapper which drives a progress bar by the number of bytes written,
+/// seeking the writer sets the progress to the new position.
+pub struct ProgressBarWriter<W> {
+    inner: W,
+    pbar: ProgressBar,
+}
+
+impl<W> ProgressBarWriter<W> {
+    pub(crate) fn new(inner: W, pbar: ProgressBar) -> ProgressBarWriter<W> {
+        ProgressBarWriter { inner, pbar }
+    }
+
+    /// Return the progress bar driven by the writer.
+    pub fn progress_bar(&self) -> &ProgressBar {
+        &self.pbar
+    }
+
+    /// Unwrap the writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for ProgressBarWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        // the bytes have been written, a failure of drawing mustn't make them written again.
+        let _ = self.pbar.add(n as u64);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Seek> Seek for ProgressBarWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = self.inner.seek(pos)?;
+        // the position has been moved, a failure of drawing doesn't change it.
+        let _ = self.pbar.set(pos, false);
+        Ok(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_progress_bar_reader() {
+        let (tx, _rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(1024), 0, tx);
+        let mut reader = pbar.wrap_read(Cursor::new(vec![0u8; 1024]));
+
+        let mut buf = [0u8; 100];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(pbar.position(), 100);
+
+        reader.seek(SeekFrom::Start(1000)).unwrap();
+        assert_eq!(pbar.position(), 1000);
+
+        reader.fill_buf().unwrap();
+        reader.consume(24);
+        assert_eq!(pbar.position(), 1024);
+    }
+
+    #[test]
+    fn test_progress_bar_writer() {
+        let (tx, _rx) = mpsc::channel();
+        let pbar = ProgressBar::channel(Some(1024), 0, tx);
+        let mut writer = pbar.wrap_write(Vec::new());
+
+        writer.write_all(&[0u8; 512]).unwrap();
+        assert_eq!(pbar.position(), 512);
+        assert_eq!(writer.into_inner().len(), 512);
+    }
+
+    #[test]
+    fn test_draw_failure_keeps_io_result() {
+        let (tx, rx) = mpsc::channel();
+        drop(rx);
+        let pbar = ProgressBar::channel(Some(1024), 0, tx);
+        let mut reader = pbar.wrap_read(Cursor::new(vec![0u8; 1024]));
+        let mut buf = [0u8; 100];
+        assert_eq!(reader.read(&mut buf).unwrap(), 100);
+        assert_eq!(reader.seek(SeekFrom::Start(1000)).unwrap(), 1000);
+
+        let mut writer = pbar.wrap_write(Vec::new());
+        writer.write_all(&[0u8; 512]).unwrap();
+        assert_eq!(writer.into_inner().len(), 512);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::bar::*;
+use crate::error::*;
+use crate::style::*;
+
+/// Wrap an iterator with a progress bar, which is increased for every item.
+pub trait ProgressIterator: Iterator + Sized {
+    /// Wrap the iterator with a default style progress bar on stdout,
+    /// the total is taken from the `size_hint` of the iterator when it's exact,
+    /// otherwise the progress bar is indeterminate.
+    fn progress(self) -> ProgressBarIter<Self> {
+        let pbar = ProgressBar::with_target(ProgressBarTarget::stdout(), exact_len(&self));
+        self.progress_with_bar(pbar)
+    }
+
+    /// Wrap the iterator with a progress bar on the given target with the given style,
+    /// return `Error::InvalidStyle` if the style is invalid.
+    fn progress_with(
+        self,
+        target: ProgressBarTarget,
+        style: ProgressBarStyle,
+    ) -> Result<ProgressBarIter<Self>> {
+        let pbar = ProgressBar::with_target(target, exact_len(&self));
+        pbar.set_style(style)?;
+        Ok(self.progress_with_bar(pbar))
+    }
+
+    /// Wrap the iterator with an existing progress bar.
+    fn progress_with_bar(self, pbar: ProgressBar) -> ProgressBarIter<Self> {
+        ProgressBarIter {
+            iter: self,
+            pbar,
+            finished: false,
+        }
+    }
+}
+
+impl<I: Iterator> ProgressIterator for I {}
+
+/// Return the length of the iterator if its `size_hint` is exact.
+fn exact_len<I: Iterator>(iter: &I) -> Option<u64> {
+    match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(lower as u64),
+        _ => None,
+    }
+}
+
+/// An iterator wrapper which drives a progress bar,
+/// the progress bar is finished when the iterator is exhausted or dropped.
+pub struct ProgressBarIter<I> {
+    iter: I,
+    pbar: ProgressBar,
+    finished: bool,
+}
+
+impl<I> ProgressBarIter<I> {
+    /// Return the progress bar driven by the iterator.
+    pub fn progress_bar(&self) -> &ProgressBar {
+        &self.pbar
+    }
+
+    fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            // the failures of drawing can't be returned from the iterator.
+            let _ = self.pbar.finish();
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for ProgressBarIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next() {
+            Some(item) => {
+                let _ = self.pbar.inc();
+                Some(item)
+            }
+            None => {
+                self.finish();
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for ProgressBarIter<I> {}
+
+impl<I> Drop for ProgressBarIter<I> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn is_done(event: &ProgressBarEvent) -> bool {
+        matches!(event, ProgressBarEvent::Draw(_, info) if info.done)
+    }
+
+    #[test]
+    fn test_progress_iterator() {
+        let (tx, rx) = mpsc::channel();
+        let iter = (0..10)
+            .progress_with(
+                ProgressBarTarget::channel(0, tx.clone()),
+                ProgressBarStyle::default(),
+            )
+            .unwrap();
+        assert_eq!(iter.progress_bar().total(), Some(10));
+        assert_eq!(iter.sum::<i32>(), 45);
+        assert_eq!(rx.try_iter().filter(is_done).count(), 1);
+
+        let mut iter = (0..10)
+            .filter(|i| i % 2 == 0)
+            .progress_with(
+                ProgressBarTarget::channel(0, tx.clone()),
+                ProgressBarStyle::default(),
+            )
+            .unwrap();
+        assert_eq!(iter.progress_bar().total(), None);
+        iter.next();
+        let pbar = iter.progress_bar().clone();
+        drop(iter);
+        assert_eq!(pbar.position(), 1);
+        assert_eq!(rx.try_iter().filter(is_done).count(), 1);
+
+        let mut style = ProgressBarStyle::customizable();
+        style.layout.push(Component::Bar(
+            BarSymbols::new("[", "", ">", "-", "]"),
+            BarWidth::Fill,
+        ));
+        let result = (0..10).progress_with(ProgressBarTarget::channel(0, tx), style);
+        assert!(matches!(result, Err(Error::InvalidStyle(_))));
+    }
+}
@@ -2,13 +2,33 @@
 mod term;
 
 mod bar;
+mod color;
+mod error;
+mod estimator;
 mod format;
+mod io;
+mod iter;
 mod multi;
+mod spinner;
 mod style;
+mod template;
 mod util;
 
 pub use self::term::Term;
-pub use self::bar::ProgressBar;
+pub use self::bar::{
+    LogInterval, LogMode, NonInteractiveMode, ProgressBar, ProgressBarDrawInfo, ProgressBarEvent,
+    ProgressBarTarget,
+};
+pub use self::color::{Color, TextStyle};
+pub use self::error::{Error, ErrorPolicy, Result};
+pub use self::estimator::{
+    AverageEstimator, Estimator, EwmaEstimator, RegressionEstimator, WindowEstimator,
+};
 pub use self::format::{TimeFormat, UnitFormat};
+pub use self::io::{ProgressBarReader, ProgressBarWriter};
+pub use self::iter::{ProgressBarIter, ProgressIterator};
 pub use self::multi::MultiProgressBar;
-pub use self::style::ProgressBarStyle;
+pub use self::spinner::Spinner;
+pub use self::style::{
+    Alignment, BarSymbols, BarWidth, Component, ProgressBarStyle, RenderComponent, RenderContext,
+};
@@ -1,81 +1,365 @@
-use std::io;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
 
 use crate::bar::*;
+use crate::error::*;
+use crate::util::*;
 
-pub struct MultiProgressBar {
-    target: ProgressBarTarget,
+struct MultiProgressBarState {
     bars: Vec<String>,
+    // whether the progress bars are finished, a reset progress bar is unfinished again.
+    finished: Vec<bool>,
     nbars: usize,
-    tx: mpsc::Sender<(usize, ProgressBarDrawInfo)>,
-    rx: mpsc::Receiver<(usize, ProgressBarDrawInfo)>,
+    // whether the progress bars have been drawn on the target.
+    drawn: bool,
+    // the depth of `suspend`, the progress bars aren't drawn while it's positive.
+    suspended: usize,
+    // the widths of the lines drawn, to count the rows taken after the terminal is resized.
+    drawn_widths: Vec<usize>,
+    error_policy: ErrorPolicy,
+}
+
+pub struct MultiProgressBar {
+    // the target and the non-interactive mode, shared with the attached progress bars.
+    shared: Arc<MultiTarget>,
+    state: Mutex<MultiProgressBarState>,
+    tx: mpsc::Sender<ProgressBarEvent>,
+    rx: Mutex<mpsc::Receiver<ProgressBarEvent>>,
 }
 
 impl MultiProgressBar {
     pub fn stdout() -> MultiProgressBar {
-        let (tx, rx) = mpsc::channel();
-        MultiProgressBar {
-            target: ProgressBarTarget::stdout(),
-            bars: vec![],
-            nbars: 0,
-            tx,
-            rx,
-        }
+        MultiProgressBar::with_target(ProgressBarTarget::stdout())
     }
 
     pub fn stderr() -> MultiProgressBar {
+        MultiProgressBar::with_target(ProgressBarTarget::stderr())
+    }
+
+    fn with_target(target: ProgressBarTarget) -> MultiProgressBar {
         let (tx, rx) = mpsc::channel();
         MultiProgressBar {
-            target: ProgressBarTarget::stderr(),
-            bars: vec![],
-            nbars: 0,
+            shared: Arc::new(MultiTarget::new(target)),
+            state: Mutex::new(MultiProgressBarState {
+                bars: vec![],
+                finished: vec![],
+                nbars: 0,
+                drawn: false,
+                suspended: 0,
+                drawn_widths: vec![],
+                error_policy: ErrorPolicy::default(),
+            }),
             tx,
-            rx,
+            rx: Mutex::new(rx),
         }
     }
 
-    pub fn attach(&mut self, total: u64) -> ProgressBar {
+    /// Set how to handle the failures of drawing, default policy is `ErrorPolicy::Surface`.
+    ///
+    /// The attached progress bars have their own policies.
+    pub fn set_error_policy(&self, policy: ErrorPolicy) -> &Self {
+        self.state().error_policy = policy;
+        self
+    }
+
+    /// Set how to draw the progress bars if the terminal is not interactive,
+    /// default mode is `NonInteractiveMode::Hidden`.
+    ///
+    /// In `NonInteractiveMode::Log`, every attached progress bar prints its own full lines
+    /// as the log target does, the mode replaces the ones of the attached progress bars.
+    pub fn set_non_interactive_mode(&self, mode: NonInteractiveMode) -> &Self {
+        *self
+            .shared
+            .non_interactive
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = mode;
+        self
+    }
+
+    pub fn attach(&self, total: u64) -> ProgressBar {
+        self.attach_with_total(Some(total))
+    }
+
+    /// Attach an indeterminate progress bar (without total).
+    pub fn attach_indeterminate(&self) -> ProgressBar {
+        self.attach_with_total(None)
+    }
+
+    fn attach_with_total(&self, total: Option<u64>) -> ProgressBar {
+        let mut state = self.state();
         // index from 0 to bars.len()-1
-        let index = self.bars.len();
-        self.bars.push(String::new());
-        self.nbars += 1;
-        ProgressBar::channel(total, index, self.tx.clone())
+        let index = state.bars.len();
+        state.bars.push(String::new());
+        state.finished.push(false);
+        state.nbars += 1;
+        let target = ProgressBarTarget::attached(index, self.tx.clone(), self.shared.clone());
+        ProgressBar::with_target(target, total)
+    }
+
+    /// Print `msg` as a permanent line above the progress bars without corrupting them.
+    pub fn println(&self, msg: &str) -> Result<()> {
+        let mut state = self.state();
+        let result = self.redraw(&mut state, Some(msg));
+        state.error_policy.check(result)
+    }
+
+    /// Erase all progress bars, run `f` with a clean terminal, then redraw them.
+    ///
+    /// The progress bars aren't drawn while `f` is running even if they're updated.
+    /// The result of `f` is always returned,
+    /// with the failures of erasing and redrawing checked by the error policy.
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> (R, Result<()>) {
+        let (drawn, erased) = {
+            let mut state = self.state();
+            state.suspended += 1;
+            (state.drawn, self.clear(&mut state))
+        };
+
+        // run without the state lock, the attached progress bars may be updated meanwhile.
+        let result = f();
+
+        let mut state = self.state();
+        state.suspended -= 1;
+        let redrawn = match drawn && state.suspended == 0 {
+            true => self.redraw(&mut state, None),
+            false => Ok(()),
+        };
+        (result, state.error_policy.check(erased.and(redrawn)))
     }
 
-    pub fn join(&mut self) -> io::Result<()> {
+    pub fn join(&self) -> Result<()> {
         self.listen()
     }
 
-    pub fn join_with_msg(&mut self, msg: &str) -> io::Result<()> {
-        self.listen().unwrap();
-        self.target.draw(msg)
+    pub fn join_with_msg(&self, msg: &str) -> Result<()> {
+        self.listen()?;
+        let result = self.shared.target.draw(msg);
+        self.state().error_policy.check(result)
     }
 
-    fn listen(&mut self) -> io::Result<()> {
-        let mut first = true;
+    fn listen(&self) -> Result<()> {
+        let rx = self.rx.lock().unwrap_or_else(|e| e.into_inner());
+        *self.shared.listening() = true;
 
-        while self.nbars > 0 {
-            let (index, info) = self.rx.recv().unwrap();
-            self.bars[index] = info.line;
+        let mut result = Ok(());
+        while result.is_ok() && self.state().nbars > 0 {
+            // never disconnected, the sender is owned by self.
+            result = rx
+                .recv()
+                .map_err(|_| Error::Disconnected)
+                .and_then(|event| self.handle(event));
+        }
 
-            if !first {
-                self.target.move_cursor_up(self.bars.len());
-            } else {
-                first = false;
-            }
+        // the progress bars may have sent a suspend event before it stops listening,
+        // reply to them, or they would wait forever.
+        *self.shared.listening() = false;
+        while let Ok(event) = rx.try_recv() {
+            let handled = self.handle(event);
+            result = result.and(handled);
+        }
+        result
+    }
 
-            let mut out = String::new();
-            for bar in &self.bars {
-                out.push_str(&format!("\r{}\n", bar));
+    fn handle(&self, event: ProgressBarEvent) -> Result<()> {
+        let mut state = self.state();
+        let result = match event {
+            ProgressBarEvent::Draw(index, info) => {
+                state.bars[index] = info.line;
+                // a progress bar may be finished again after being reset.
+                match (info.done, state.finished[index]) {
+                    (true, false) => state.nbars -= 1,
+                    (false, true) => state.nbars += 1,
+                    _ => {}
+                }
+                state.finished[index] = info.done;
+                self.redraw(&mut state, None)
             }
+            ProgressBarEvent::Println(msg) => self.redraw(&mut state, Some(&msg)),
+            ProgressBarEvent::Suspend(ack, resume) => {
+                let drawn = state.drawn;
+                let erased = self.clear(&mut state);
+                let _ = ack.send(());
+                let _ = resume.recv();
+                match drawn {
+                    true => erased.and(self.redraw(&mut state, None)),
+                    false => erased,
+                }
+            }
+        };
+        state.error_policy.check(result)
+    }
 
-            self.target.draw(&out).unwrap();
-
-            if info.done {
-                self.nbars -= 1;
+    /// Redraw all progress bars, and print `msg` above them if any.
+    fn redraw(&self, state: &mut MultiProgressBarState, msg: Option<&str>) -> Result<()> {
+        if !self.shared.target.is_interactive() || state.suspended > 0 {
+            return self.redraw_lines(msg);
+        }
+        let width = self.shared.target.terminal_width();
+        if state.drawn {
+            let n = state.drawn_widths.len();
+            // the lines are wrapped if the terminal has been narrowed, erase the whole block.
+            if rows(&state.drawn_widths, width) > n {
+                self.clear(state)?;
+            } else if n > 0 {
+                self.shared.target.move_cursor_up(n)?;
             }
         }
+        // the progress bars are considered as drawn even if the drawing failed.
+        state.drawn = true;
+        state.drawn_widths = state
+            .bars
+            .iter()
+            .map(|bar| display_width(&truncate_to_width(bar, width)))
+            .collect();
 
+        if let Some(msg) = msg {
+            self.shared.target.clear_line()?;
+            self.shared.target.draw(&format!("{}\n", msg))?;
+        }
+        for bar in &state.bars {
+            // the lines are shifted down by the message, clear the stale content.
+            if msg.is_some() {
+                self.shared.target.clear_line()?;
+            }
+            self.shared
+                .target
+                .draw(&format!("\r{}\n", truncate_to_width(bar, width)))?;
+        }
         Ok(())
     }
+
+    /// Print `msg` as a full line on the non-interactive terminal or while suspended,
+    /// the full lines of the progress bars are sent as messages too.
+    fn redraw_lines(&self, msg: Option<&str>) -> Result<()> {
+        match msg {
+            Some(msg) => self.shared.target.draw(&format!("{}\n", msg)),
+            None => Ok(()),
+        }
+    }
+
+    /// Erase all progress bars and move the cursor back to the first line.
+    fn clear(&self, state: &mut MultiProgressBarState) -> Result<()> {
+        let n = rows(&state.drawn_widths, self.shared.target.terminal_width());
+        if !state.drawn || n == 0 || !self.shared.target.is_interactive() {
+            return Ok(());
+        }
+        state.drawn = false;
+
+        self.shared.target.move_cursor_up(n)?;
+        for _ in 0..n {
+            self.shared.target.clear_line()?;
+            self.shared.target.draw("\n")?;
+        }
+        self.shared.target.move_cursor_up(n)
+    }
+
+    fn state(&self) -> MutexGuard<'_, MultiProgressBarState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Count the rows taken by the lines of `widths` on a terminal of `width` columns,
+/// supposing the terminal rewraps the lines once it's narrowed.
+fn rows(widths: &[usize], width: usize) -> usize {
+    widths
+        .iter()
+        .map(|&w| w.div_ceil(width.max(1)).max(1))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rows() {
+        assert_eq!(rows(&[10, 0, 80], 80), 3);
+        assert_eq!(rows(&[10, 0, 80], 40), 4);
+        assert_eq!(rows(&[10, 0, 80], 30), 5);
+    }
+
+    #[test]
+    fn test_reset_finished_bar() {
+        let mpb = MultiProgressBar::stdout();
+        let a = mpb.attach(10);
+        let b = mpb.attach(10);
+        let b_finished = Arc::new(AtomicBool::new(false));
+
+        let flag = b_finished.clone();
+        let handle = thread::spawn(move || {
+            a.finish().unwrap();
+            a.reset().unwrap();
+            a.finish().unwrap();
+            thread::sleep(Duration::from_millis(50));
+            flag.store(true, Ordering::SeqCst);
+            b.finish().unwrap();
+        });
+        mpb.join().unwrap();
+        assert!(b_finished.load(Ordering::SeqCst));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_suspend_across_bars() {
+        let (tx, _rx) = mpsc::channel();
+        let mpb = Arc::new(MultiProgressBar::with_target(ProgressBarTarget::channel(
+            0, tx,
+        )));
+        let a = mpb.attach(10);
+        let b = mpb.attach(10);
+        let listener = {
+            let mpb = mpb.clone();
+            thread::spawn(move || mpb.join())
+        };
+        while !*mpb.shared.listening() {
+            thread::yield_now();
+        }
+
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::spawn(move || {
+            // `a` is suspended while `b` is, and `b` updates `a` meanwhile.
+            let (suspending, redrawn) = b.suspend(|| {
+                let suspended = a.clone();
+                let suspending = thread::spawn(move || suspended.suspend(|| ()).1);
+                thread::sleep(Duration::from_millis(50));
+                a.set(5, true).unwrap();
+                suspending
+            });
+            redrawn.unwrap();
+            suspending.join().unwrap().unwrap();
+
+            // the attached progress bars can be suspended while MultiProgressBar is.
+            let (redrawn, multi_redrawn) = mpb.suspend(|| a.suspend(|| ()).1);
+            redrawn.unwrap();
+            multi_redrawn.unwrap();
+            a.finish().unwrap();
+            b.finish().unwrap();
+            done_tx.send(()).unwrap();
+        });
+        done_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("suspending the progress bars deadlocked");
+        listener.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_suspend_without_join() {
+        let (tx, _rx) = mpsc::channel();
+        let mpb = MultiProgressBar::with_target(ProgressBarTarget::channel(0, tx));
+        let pbar = mpb.attach(10);
+        let (result, redrawn) = pbar.suspend(|| 42);
+        assert_eq!(result, 42);
+        redrawn.unwrap();
+
+        let handle = thread::spawn(move || {
+            pbar.suspend(|| pbar.set(5, true)).0.unwrap();
+            pbar.finish().unwrap();
+        });
+        mpb.join().unwrap();
+        handle.join().unwrap();
+        assert!(!*mpb.shared.listening());
+    }
 }
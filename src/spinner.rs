@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The frames of the spinner component, which advance every `interval`
+/// regardless of the refresh rate, and the glyph shown once the progress bar is finished.
+///
+/// The frames only move when the progress bar is redrawn,
+/// use `ProgressBar::enable_steady_tick` to keep spinning while the progress is idle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Spinner {
+    pub frames: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(rename = "interval_ms", with = "millis"))]
+    pub interval: Duration,
+    pub done: String,
+}
+
+impl Default for Spinner {
+    /// Return the braille dots spinner.
+    fn default() -> Spinner {
+        Spinner::braille()
+    }
+}
+
+impl Spinner {
+    /// Return a spinner with the custom frames, the done glyph is "✔".
+    pub fn new(frames: &[&str], interval: Duration) -> Spinner {
+        Spinner {
+            frames: frames.iter().map(|frame| frame.to_string()).collect(),
+            interval,
+            done: "✔".to_string(),
+        }
+    }
+
+    /// Return the spinner of braille dots, `⠋ ⠙ ⠹ ⠸ ⠼ ⠴ ⠦ ⠧ ⠇ ⠏`.
+    pub fn braille() -> Spinner {
+        Spinner::new(
+            &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+            Duration::from_millis(80),
+        )
+    }
+
+    /// Return the spinner of ASCII lines, `- \ | /`, the done glyph is "*".
+    pub fn line() -> Spinner {
+        Spinner::new(&["-", "\\", "|", "/"], Duration::from_millis(130)).done("*")
+    }
+
+    /// Return the spinner of arrows, `← ↖ ↑ ↗ → ↘ ↓ ↙`.
+    pub fn arrows() -> Spinner {
+        Spinner::new(
+            &["←", "↖", "↑", "↗", "→", "↘", "↓", "↙"],
+            Duration::from_millis(100),
+        )
+    }
+
+    /// Return the spinner of clock emoji, the done glyph is "✅" which takes 2 columns as well.
+    pub fn clock() -> Spinner {
+        Spinner::new(
+            &[
+                "🕛", "🕐", "🕑", "🕒", "🕓", "🕔", "🕕", "🕖", "🕗", "🕘", "🕙", "🕚",
+            ],
+            Duration::from_millis(100),
+        )
+        .done("✅")
+    }
+
+    /// Set the interval between two frames.
+    pub fn interval(mut self, interval: Duration) -> Spinner {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the glyph shown once the progress bar is finished.
+    pub fn done(mut self, glyph: &str) -> Spinner {
+        self.done = glyph.to_string();
+        self
+    }
+
+    /// Return the frame shown after `elapsed`, or the done glyph if `finished`.
+    pub(crate) fn frame(&self, elapsed: Duration, finished: bool) -> &str {
+        if finished || self.frames.is_empty() {
+            return &self.done;
+        }
+        let interval = self.interval.as_nanos().max(1);
+        let index = (elapsed.as_nanos() / interval) % self.frames.len() as u128;
+        &self.frames[index as usize]
+    }
+}
+
+/// (De)serialize the interval as the number of milliseconds.
+#[cfg(feature = "serde")]
+mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(interval: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(interval.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame() {
+        let spinner = Spinner::line();
+        let frame = |ms| spinner.frame(Duration::from_millis(ms), false);
+        assert_eq!(frame(0), "-");
+        assert_eq!(frame(129), "-");
+        assert_eq!(frame(130), "\\");
+        assert_eq!(frame(3 * 130), "/");
+        assert_eq!(frame(4 * 130), "-");
+        assert_eq!(spinner.frame(Duration::from_millis(130), true), "*");
+
+        let spinner = Spinner::new(&["a", "b"], Duration::from_secs(1)).done("ok");
+        assert_eq!(spinner.frame(Duration::from_millis(1500), false), "b");
+        assert_eq!(spinner.frame(Duration::from_millis(1500), true), "ok");
+    }
+}
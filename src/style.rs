@@ -1,39 +1,636 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::color::*;
+use crate::error::*;
+use crate::estimator::*;
 use crate::format::*;
+use crate::spinner::*;
+use crate::template::*;
+use crate::util::*;
 
+/// A component of the progress bar style.
+///
+/// With the 'serde' feature, components are (de)serialized as snake case names,
+/// like `"percent"` or `{ "bar": { "symbols": { "begin": "[", "fill": "#", ... }, "width": 30 } }`,
+/// except the custom ones.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "ComponentRepr"))]
 pub enum Component {
     Counter(String, UnitFormat), // layout - Current Str("/") Total
     Speed(UnitFormat),
     Percent,
-    Bar(Vec<char>, usize),
+    Bar(BarSymbols, BarWidth),
+    /// A bar with eighth blocks for the partial cell, ASCII if the locale is not UTF-8.
+    SmoothBar(BarWidth),
+    TimeLeft(TimeFormat),
+    TimeElapsed(TimeFormat),
+    TimeTotal(TimeFormat),
+    Str(String),
+    Message,
+    /// The title of the progress bar, which isn't drawn before the components then.
+    Title,
+    /// The current value.
+    Position(UnitFormat),
+    /// The total value, '?' if the progress bar is indeterminate.
+    Total(UnitFormat),
+    /// The percent number without the sign, '?' if the progress bar is indeterminate.
+    PercentValue,
+    /// A component padded to the width in columns.
+    Padded(Box<Component>, Alignment, usize),
+    /// A spinner showing the liveness, with the done glyph once finished.
+    Spinner(Spinner),
+    /// A component rendered by user, which can't be (de)serialized.
+    Custom(Arc<dyn RenderComponent>),
+    /// A component drawn with the text style,
+    /// the empty part of a bar is drawn with the second style if any.
+    Styled(Box<Component>, TextStyle, Option<TextStyle>),
+}
+
+impl Component {
+    /// Return the component without the text styles, and the styles.
+    pub(crate) fn unstyled(&self) -> (&Component, TextStyle, Option<TextStyle>) {
+        match self {
+            Component::Styled(component, style, empty) => {
+                let (component, _, _) = component.unstyled();
+                (component, *style, *empty)
+            }
+            component => (component, TextStyle::default(), None),
+        }
+    }
+
+    /// Return the component without the text styles and the padding.
+    pub(crate) fn base(&self) -> &Component {
+        match self {
+            Component::Styled(component, ..) | Component::Padded(component, ..) => component.base(),
+            component => component,
+        }
+    }
+}
+
+/// The (de)serialized component, with the named fields instead of the positional ones.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum ComponentRepr {
+    Counter {
+        delimiter: String,
+        format: UnitFormat,
+    },
+    Speed(UnitFormat),
+    Percent,
+    Bar {
+        symbols: BarSymbols,
+        width: BarWidth,
+    },
+    SmoothBar(BarWidth),
     TimeLeft(TimeFormat),
     TimeElapsed(TimeFormat),
     TimeTotal(TimeFormat),
     Str(String),
+    Message,
+    Title,
+    Position(UnitFormat),
+    Total(UnitFormat),
+    PercentValue,
+    Padded {
+        component: Box<Component>,
+        alignment: Alignment,
+        width: usize,
+    },
+    Spinner(Spinner),
+    Styled {
+        component: Box<Component>,
+        style: TextStyle,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        empty: Option<TextStyle>,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl From<ComponentRepr> for Component {
+    fn from(repr: ComponentRepr) -> Component {
+        match repr {
+            ComponentRepr::Counter { delimiter, format } => Component::Counter(delimiter, format),
+            ComponentRepr::Speed(format) => Component::Speed(format),
+            ComponentRepr::Percent => Component::Percent,
+            ComponentRepr::Bar { symbols, width } => Component::Bar(symbols, width),
+            ComponentRepr::SmoothBar(width) => Component::SmoothBar(width),
+            ComponentRepr::TimeLeft(format) => Component::TimeLeft(format),
+            ComponentRepr::TimeElapsed(format) => Component::TimeElapsed(format),
+            ComponentRepr::TimeTotal(format) => Component::TimeTotal(format),
+            ComponentRepr::Str(s) => Component::Str(s),
+            ComponentRepr::Message => Component::Message,
+            ComponentRepr::Title => Component::Title,
+            ComponentRepr::Position(format) => Component::Position(format),
+            ComponentRepr::Total(format) => Component::Total(format),
+            ComponentRepr::PercentValue => Component::PercentValue,
+            ComponentRepr::Padded {
+                component,
+                alignment,
+                width,
+            } => Component::Padded(component, alignment, width),
+            ComponentRepr::Spinner(spinner) => Component::Spinner(spinner),
+            ComponentRepr::Styled {
+                component,
+                style,
+                empty,
+            } => Component::Styled(component, style, empty),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Component {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let repr = match self.clone() {
+            Component::Counter(delimiter, format) => ComponentRepr::Counter { delimiter, format },
+            Component::Speed(format) => ComponentRepr::Speed(format),
+            Component::Percent => ComponentRepr::Percent,
+            Component::Bar(symbols, width) => ComponentRepr::Bar { symbols, width },
+            Component::SmoothBar(width) => ComponentRepr::SmoothBar(width),
+            Component::TimeLeft(format) => ComponentRepr::TimeLeft(format),
+            Component::TimeElapsed(format) => ComponentRepr::TimeElapsed(format),
+            Component::TimeTotal(format) => ComponentRepr::TimeTotal(format),
+            Component::Str(s) => ComponentRepr::Str(s),
+            Component::Message => ComponentRepr::Message,
+            Component::Title => ComponentRepr::Title,
+            Component::Position(format) => ComponentRepr::Position(format),
+            Component::Total(format) => ComponentRepr::Total(format),
+            Component::PercentValue => ComponentRepr::PercentValue,
+            Component::Padded(component, alignment, width) => ComponentRepr::Padded {
+                component,
+                alignment,
+                width,
+            },
+            Component::Spinner(spinner) => ComponentRepr::Spinner(spinner),
+            Component::Custom(_) => {
+                return Err(serde::ser::Error::custom(
+                    "custom components can't be serialized",
+                ))
+            }
+            Component::Styled(component, style, empty) => ComponentRepr::Styled {
+                component,
+                style,
+                empty,
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+/// A component rendered by user, like the number of failed files or the queue depth.
+///
+/// It's implemented by the closures taking the `RenderContext` and returning the text.
+pub trait RenderComponent: Send + Sync {
+    /// Return the text of the component, which is drawn with the other components.
+    fn render(&self, ctxt: &RenderContext<'_>) -> String;
+}
+
+impl<F> RenderComponent for F
+where
+    F: Fn(&RenderContext<'_>) -> String + Send + Sync,
+{
+    fn render(&self, ctxt: &RenderContext<'_>) -> String {
+        self(ctxt)
+    }
+}
+
+/// The read-only state of the progress bar given to `RenderComponent`.
+pub struct RenderContext<'a> {
+    pub(crate) position: u64,
+    pub(crate) total: Option<u64>,
+    pub(crate) elapsed: Duration,
+    pub(crate) estimator: &'a dyn Estimator,
+    pub(crate) message: &'a str,
+}
+
+impl RenderContext<'_> {
+    /// Return current value of the progress bar.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Return total value of the progress bar, `None` if it's indeterminate.
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Return the elapsed time, excluding the paused time.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Return the estimator of the speed and the time left.
+    pub fn estimator(&self) -> &dyn Estimator {
+        self.estimator
+    }
+
+    /// Return the message set by `ProgressBar::set_message`.
+    pub fn message(&self) -> &str {
+        self.message
+    }
+}
+
+/// The alignment of a padded component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
 }
 
+/// The symbols of the bar component, every part can be several characters or empty.
+///
+/// An empty part isn't drawn, except the empty part of the bar which is drawn as spaces,
+/// and the fill part must not be empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct BarSymbols {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub begin: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fill: String,
+    /// The symbol after the filled part, at the current position.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub head: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub empty: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub end: String,
+}
+
+impl Default for BarSymbols {
+    /// Return the symbols "[#>-]".
+    fn default() -> BarSymbols {
+        BarSymbols::new("[", "#", ">", "-", "]")
+    }
+}
+
+impl FromStr for BarSymbols {
+    type Err = Error;
+
+    /// Parse the 5 symbols of begin/fill/head/empty/end, like "[#>-]".
+    fn from_str(s: &str) -> Result<BarSymbols> {
+        let symbols = s.chars().map(String::from).collect::<Vec<_>>();
+        match symbols.as_slice() {
+            [begin, fill, head, empty, end] => Ok(BarSymbols::new(begin, fill, head, empty, end)),
+            _ => Err(Error::InvalidStyle(format!(
+                "bar needs 5 symbols (begin/fill/head/empty/end), got {:?}",
+                s
+            ))),
+        }
+    }
+}
+
+impl BarSymbols {
+    /// Return the bar symbols, like `BarSymbols::new("", "━", "╸", "━", "")` without the ends.
+    pub fn new(begin: &str, fill: &str, head: &str, empty: &str, end: &str) -> BarSymbols {
+        BarSymbols {
+            begin: begin.to_string(),
+            fill: fill.to_string(),
+            head: head.to_string(),
+            empty: empty.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    /// Check that the bar can be drawn, the fill part must take some columns.
+    pub fn validate(&self) -> Result<()> {
+        if display_width(&self.fill) == 0 {
+            return Err(Error::InvalidStyle(format!(
+                "bar fill symbol must take some columns, got {:?}",
+                self.fill
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The width of the bar component, in columns, excluding the begin and end symbols.
+///
+/// With the 'serde' feature, the width is (de)serialized as the number or `"fill"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "BarWidthRepr", into = "BarWidthRepr"))]
+pub enum BarWidth {
+    Fixed(usize),
+    /// Take all columns left by the other components, so that the line never wraps.
+    Fill,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BarWidthRepr {
+    Fixed(usize),
+    Fill(FillWidth),
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum FillWidth {
+    Fill,
+}
+
+#[cfg(feature = "serde")]
+impl From<BarWidthRepr> for BarWidth {
+    fn from(repr: BarWidthRepr) -> BarWidth {
+        match repr {
+            BarWidthRepr::Fixed(width) => BarWidth::Fixed(width),
+            BarWidthRepr::Fill(FillWidth::Fill) => BarWidth::Fill,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<BarWidth> for BarWidthRepr {
+    fn from(width: BarWidth) -> BarWidthRepr {
+        match width {
+            BarWidth::Fixed(width) => BarWidthRepr::Fixed(width),
+            BarWidth::Fill => BarWidthRepr::Fill(FillWidth::Fill),
+        }
+    }
+}
+
+/// The layout of the progress bar.
+///
+/// With the 'serde' feature, the style is (de)serialized as a map of 'layout' and 'separator',
+/// and the deserialized layout is validated.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
 pub struct ProgressBarStyle {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_layout"))]
     pub layout: Vec<Component>,
+    /// The string drawn after every component, default is " ".
+    #[cfg_attr(feature = "serde", serde(default = "default_separator"))]
+    pub separator: String,
+}
+
+#[cfg(feature = "serde")]
+fn default_separator() -> String {
+    " ".to_string()
 }
 
+/// Deserialize the layout, rejecting the layout which can't be drawn.
+#[cfg(feature = "serde")]
+fn deserialize_layout<'de, D>(deserializer: D) -> std::result::Result<Vec<Component>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let style = ProgressBarStyle {
+        layout: Vec::deserialize(deserializer)?,
+        separator: String::new(),
+    };
+    style.validate().map_err(serde::de::Error::custom)?;
+    Ok(style.layout)
+}
+
+impl Default for ProgressBarStyle {
+    /// Return a default progress bar style, which is the 'ascii' preset.
+    fn default() -> ProgressBarStyle {
+        ProgressBarStyle::ascii()
+    }
+}
+
+/// The names of the preset styles, in the order of `ProgressBarStyle::presets`.
+const PRESETS: &[&str] = &[
+    "ascii",
+    "blocks",
+    "pip",
+    "cargo",
+    "dots",
+    "minimal",
+    "bytes_transfer",
+];
+
 impl ProgressBarStyle {
-    /// Return a default progress bar style,
+    /// Return the names of the preset styles, like "ascii" or "cargo",
+    /// which can be offered by a command line option.
+    pub fn presets() -> &'static [&'static str] {
+        PRESETS
+    }
+
+    /// Return the preset style with the name, `None` if there is no such preset.
+    pub fn preset(name: &str) -> Option<ProgressBarStyle> {
+        let style = match name {
+            "ascii" => ProgressBarStyle::ascii(),
+            "blocks" => ProgressBarStyle::blocks(),
+            "pip" => ProgressBarStyle::pip(),
+            "cargo" => ProgressBarStyle::cargo(),
+            "dots" => ProgressBarStyle::dots(),
+            "minimal" => ProgressBarStyle::minimal(),
+            "bytes_transfer" => ProgressBarStyle::bytes_transfer(),
+            _ => return None,
+        };
+        Some(style)
+    }
+
+    /// Return the preset style drawn with ASCII only,
     /// including 'Counter','Percent','Bar','TimeLeft' components.
-    pub fn default() -> ProgressBarStyle {
+    ///
+    /// `42 / 100  42% [############>-----------------] 00:03`
+    pub fn ascii() -> ProgressBarStyle {
+        ProgressBarStyle {
+            layout: vec![
+                Component::Counter("/".to_string(), UnitFormat::Default),
+                Component::Percent,
+                Component::Bar(BarSymbols::default(), BarWidth::Fixed(30)),
+                Component::TimeLeft(TimeFormat::Fmt1),
+            ],
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Return the preset style drawn with block elements,
+    /// including 'Percent','Bar','Counter','TimeLeft' components.
+    ///
+    /// ` 42% ▕████████████░░░░░░░░░░░░░░░░░░▏ 42 / 100 00:03`
+    pub fn blocks() -> ProgressBarStyle {
         ProgressBarStyle {
             layout: vec![
+                Component::Percent,
+                Component::Bar(BarSymbols::new("▕", "█", "", "░", "▏"), BarWidth::Fixed(30)),
                 Component::Counter("/".to_string(), UnitFormat::Default),
+                Component::TimeLeft(TimeFormat::Fmt1),
+            ],
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Return the preset style like the downloads of pip, in bytes,
+    /// including colored 'Bar','Counter','Speed','TimeLeft' components.
+    ///
+    /// `━━━━━━━━━━━━━━━━╸━━━━━━━━━━━━━━━━━━━━━━━ 4.2MiB / 10.0MiB   1.4MiB/s eta 00:04`
+    pub fn pip() -> ProgressBarStyle {
+        let styled = |component, style| Component::Styled(Box::new(component), style, None);
+        ProgressBarStyle {
+            layout: vec![
+                Component::Styled(
+                    Box::new(Component::Bar(
+                        BarSymbols::new("", "━", "╸", "━", ""),
+                        BarWidth::Fixed(40),
+                    )),
+                    TextStyle::new().fg(Color::Magenta),
+                    Some(TextStyle::new().fg(Color::BrightBlack)),
+                ),
+                styled(
+                    Component::Counter("/".to_string(), UnitFormat::Bytes),
+                    TextStyle::new().fg(Color::Green),
+                ),
+                styled(
+                    Component::Speed(UnitFormat::Bytes),
+                    TextStyle::new().fg(Color::Red),
+                ),
+                Component::Str("eta".to_string()),
+                styled(
+                    Component::TimeLeft(TimeFormat::Fmt1),
+                    TextStyle::new().fg(Color::Cyan),
+                ),
+            ],
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Return the preset style like the builds of cargo,
+    /// with the title right-aligned in bold green, and the message after the counter.
+    ///
+    /// `    Building [==========>              ] 42/100: pbar`
+    pub fn cargo() -> ProgressBarStyle {
+        let title = Component::Padded(Box::new(Component::Title), Alignment::Right, 12);
+        ProgressBarStyle {
+            layout: vec![
+                Component::Styled(
+                    Box::new(title),
+                    TextStyle::new().fg(Color::Green).bold(),
+                    None,
+                ),
+                Component::Str(" ".to_string()),
+                Component::Bar(
+                    BarSymbols::new("[", "=", ">", " ", "]"),
+                    BarWidth::Fixed(25),
+                ),
+                Component::Str(" ".to_string()),
+                Component::Position(UnitFormat::Default),
+                Component::Str("/".to_string()),
+                Component::Total(UnitFormat::Default),
+                Component::Str(": ".to_string()),
+                Component::Message,
+            ],
+            separator: String::new(),
+        }
+    }
+
+    /// Return the preset style drawn with braille dots,
+    /// including 'Bar','Percent','TimeLeft' components.
+    ///
+    /// `⢸⣿⣿⣿⣿⣿⣿⣿⣿⣿⣿⣿⣿⣷⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⣀⡇  42% 00:03`
+    pub fn dots() -> ProgressBarStyle {
+        ProgressBarStyle {
+            layout: vec![
+                Component::Bar(
+                    BarSymbols::new("⢸", "⣿", "⣷", "⣀", "⡇"),
+                    BarWidth::Fixed(30),
+                ),
+                Component::Percent,
+                Component::TimeLeft(TimeFormat::Fmt1),
+            ],
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Return the preset style with a short smooth bar and the percent only.
+    ///
+    /// `|████████▍           |  42%`
+    pub fn minimal() -> ProgressBarStyle {
+        ProgressBarStyle {
+            layout: vec![
+                Component::SmoothBar(BarWidth::Fixed(20)),
                 Component::Percent,
-                Component::Bar("[#>-]".chars().collect(), 30),
+            ],
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Return the preset style for transferring bytes,
+    /// including 'Counter','Speed','Percent','Bar','TimeLeft' components.
+    ///
+    /// `4.2MiB / 10.0MiB   1.4MiB/s  42% [############>-----------------] 00:04`
+    pub fn bytes_transfer() -> ProgressBarStyle {
+        ProgressBarStyle {
+            layout: vec![
+                Component::Counter("/".to_string(), UnitFormat::Bytes),
+                Component::Speed(UnitFormat::Bytes),
+                Component::Percent,
+                Component::Bar(BarSymbols::default(), BarWidth::Fixed(30)),
                 Component::TimeLeft(TimeFormat::Fmt1),
             ],
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Check that the style can be drawn, like the symbols of the bars.
+    pub fn validate(&self) -> Result<()> {
+        for component in &self.layout {
+            match component.base() {
+                Component::Bar(symbols, _) => symbols.validate()?,
+                Component::Spinner(spinner) if spinner.frames.is_empty() => {
+                    return Err(Error::InvalidStyle(
+                        "spinner needs at least 1 frame".to_string(),
+                    ));
+                }
+                Component::Spinner(spinner) if spinner.interval.is_zero() => {
+                    return Err(Error::InvalidStyle(
+                        "spinner interval must be positive".to_string(),
+                    ));
+                }
+                _ => {}
+            }
         }
+        Ok(())
     }
 
     /// Return a customizable progress bar style without any content.
     pub fn customizable() -> ProgressBarStyle {
-        ProgressBarStyle { layout: vec![] }
+        ProgressBarStyle {
+            layout: vec![],
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Parse a progress bar style from the template,
+    /// like "{title} {bar:40.green/dim} {pos}/{len} {percent}% eta {eta}".
+    ///
+    /// The placeholders are replaced with the components, the other text is drawn as it is,
+    /// and '{{' and '}}' are the escaped braces. The components aren't separated by spaces.
+    ///
+    /// A placeholder is like `{name:<10.cyan.bold}` with optional arguments:
+    /// - the alignment ('<', '^' or '>') and the width in columns,
+    ///   the bars only take the width (or 'fill' to take all columns left);
+    /// - the text style after '.', with color names ('green', 'bright_red', 208, '#ff8000'),
+    ///   background colors ('on_blue'), and 'bold', 'dim' or 'italic';
+    /// - the style of the empty part of the bars after '/'.
+    ///
+    /// The placeholders are 'title', 'bar', 'smooth_bar', 'pos', 'len', 'bytes', 'total_bytes',
+    /// 'counter', 'percent', 'eta', 'elapsed', 'duration', 'speed', 'bytes_per_sec', 'msg'
+    /// and 'spinner' (braille dots).
+    pub fn from_template(template: &str) -> Result<ProgressBarStyle> {
+        let style = ProgressBarStyle {
+            layout: parse_template(template)?,
+            separator: String::new(),
+        };
+        style.validate()?;
+        Ok(style)
     }
 
     /// Add 'counter' component to the style,
@@ -52,11 +649,30 @@ impl ProgressBarStyle {
         self
     }
 
-    /// Add 'bar' component to the style,
+    /// Add 'bar' component to the style with 5 symbols (begin/fill/head/empty/end),
+    /// default bar width is 30.
+    pub fn bar(&mut self, s: &str, width: Option<usize>) -> Result<&mut Self> {
+        self.bar_symbols(s.parse()?, BarWidth::Fixed(width.unwrap_or(30)))
+    }
+
+    /// Add 'bar' component taking all columns left by the other components,
+    /// the bar follows the width of the progress bar.
+    pub fn fill_bar(&mut self, s: &str) -> Result<&mut Self> {
+        self.bar_symbols(s.parse()?, BarWidth::Fill)
+    }
+
+    /// Add 'bar' component with the symbols of several characters or empty ones.
+    pub fn bar_symbols(&mut self, symbols: BarSymbols, width: BarWidth) -> Result<&mut Self> {
+        symbols.validate()?;
+        self.layout.push(Component::Bar(symbols, width));
+        Ok(self)
+    }
+
+    /// Add 'smooth_bar' component to the style, 8 times more precise than 'bar',
     /// default bar width is 30.
-    pub fn bar(&mut self, s: &str, width: Option<usize>) -> &mut Self {
+    pub fn smooth_bar(&mut self, width: Option<usize>) -> &mut Self {
         self.layout
-            .push(Component::Bar(s.chars().collect(), width.unwrap_or(30)));
+            .push(Component::SmoothBar(BarWidth::Fixed(width.unwrap_or(30))));
         self
     }
 
@@ -97,4 +713,143 @@ impl ProgressBarStyle {
         self.layout.push(Component::Str(s.to_string()));
         self
     }
+
+    /// Set the text style of the last component, like the color of the bar.
+    ///
+    /// The style is drawn only if the terminal supports colors and `NO_COLOR` isn't set.
+    pub fn styled(&mut self, style: TextStyle) -> &mut Self {
+        self.restyle(|_, empty| (style, empty));
+        self
+    }
+
+    /// Set the text style of the empty part of the last component, if it's a bar.
+    pub fn empty_styled(&mut self, style: TextStyle) -> &mut Self {
+        self.restyle(|fill, _| (fill, Some(style)));
+        self
+    }
+
+    fn restyle<F>(&mut self, f: F)
+    where
+        F: FnOnce(TextStyle, Option<TextStyle>) -> (TextStyle, Option<TextStyle>),
+    {
+        if let Some(last) = self.layout.pop() {
+            let (component, style, empty) = last.unstyled();
+            let (style, empty) = f(style, empty);
+            let component = Box::new(component.clone());
+            self.layout.push(Component::Styled(component, style, empty));
+        }
+    }
+
+    /// Add 'spinner' component to the style, default is the braille dots spinner.
+    pub fn spinner(&mut self, spinner: Option<Spinner>) -> &mut Self {
+        self.layout
+            .push(Component::Spinner(spinner.unwrap_or_default()));
+        self
+    }
+
+    /// Add a component rendered by user to the style.
+    pub fn custom<C: RenderComponent + 'static>(&mut self, component: C) -> &mut Self {
+        self.layout.push(Component::Custom(Arc::new(component)));
+        self
+    }
+
+    /// Add 'message' component to the style,
+    /// the message can be changed at any time by `ProgressBar::set_message`.
+    pub fn message(&mut self) -> &mut Self {
+        self.layout.push(Component::Message);
+        self
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_style() {
+        let json = r#"{
+            "layout": [
+                "title",
+                {
+                    "styled": {
+                        "component": { "bar": { "symbols": { "fill": "━", "head": "╸", "empty": "━" }, "width": "fill" } },
+                        "style": { "fg": "green" },
+                        "empty": { "dim": true }
+                    }
+                },
+                { "padded": { "component": { "position": "bytes" }, "alignment": "right", "width": 8 } },
+                { "str": "eta" },
+                { "time_left": "fmt2" }
+            ]
+        }"#;
+        let style: ProgressBarStyle = serde_json::from_str(json).unwrap();
+        assert_eq!(style.separator, " ");
+        assert_eq!(style.layout.len(), 5);
+        match &style.layout[1] {
+            Component::Styled(bar, style, Some(empty)) => {
+                match &**bar {
+                    Component::Bar(symbols, BarWidth::Fill) => {
+                        assert_eq!(*symbols, BarSymbols::new("", "━", "╸", "━", ""))
+                    }
+                    _ => panic!("expected fill bar"),
+                }
+                assert_eq!(*style, TextStyle::new().fg(Color::Green));
+                assert_eq!(*empty, TextStyle::new().dim());
+            }
+            _ => panic!("expected styled bar"),
+        }
+
+        let json = serde_json::to_string(&ProgressBarStyle::default()).unwrap();
+        assert_eq!(
+            json,
+            r##"{"layout":[{"counter":{"delimiter":"/","format":"default"}},"percent",{"bar":{"symbols":{"begin":"[","fill":"#","head":">","empty":"-","end":"]"},"width":30}},{"time_left":"fmt1"}],"separator":" "}"##
+        );
+        let style: ProgressBarStyle = serde_json::from_str(&json).unwrap();
+        assert_eq!(style.layout.len(), 4);
+
+        let json = r#"{ "layout": [{ "spinner": { "frames": ["-", "+"], "interval_ms": 250 } }] }"#;
+        let style: ProgressBarStyle = serde_json::from_str(json).unwrap();
+        match &style.layout[0] {
+            Component::Spinner(spinner) => {
+                assert_eq!(spinner.frames, vec!["-", "+"]);
+                assert_eq!(spinner.interval, Duration::from_millis(250));
+                assert_eq!(spinner.done, "✔");
+            }
+            _ => panic!("expected spinner"),
+        }
+    }
+
+    #[test]
+    fn test_serde_invalid_style() {
+        let err = match serde_json::from_str::<ProgressBarStyle>(
+            r#"{ "layout": [{ "bar": { "symbols": { "begin": "[", "end": "]" }, "width": "fill" } }] }"#,
+        ) {
+            Err(err) => err.to_string(),
+            Ok(_) => panic!("expected invalid style"),
+        };
+        assert!(
+            err.starts_with("invalid progress bar style: bar fill symbol must take some columns")
+        );
+        assert!(
+            serde_json::from_str::<ProgressBarStyle>(r#"{ "layout": ["sparkline"] }"#).is_err()
+        );
+        for json in [
+            r##"{ "layout": [{ "bar": { "symbols": { "fil": "#" }, "width": 30 } }] }"##,
+            r##"{ "layout": [{ "bar": { "symbols": { "fill": "#" }, "width": 30, "smooth": true } }] }"##,
+            r##"{ "layout": [{ "bar": { "symbols": { "fill": "#" }, "width": "auto" } }] }"##,
+            r#"{ "layout": [{ "styled": { "component": "percent", "style": { "colour": "red" } } }] }"#,
+            r#"{ "layout": [{ "spinner": { "frames": ["-"], "interval": 250 } }] }"#,
+            r#"{ "layout": ["percent"], "seperator": "|" }"#,
+        ] {
+            assert!(
+                serde_json::from_str::<ProgressBarStyle>(json).is_err(),
+                "{}",
+                json
+            );
+        }
+
+        let mut style = ProgressBarStyle::customizable();
+        style.custom(|_: &RenderContext<'_>| String::new());
+        assert!(serde_json::to_string(&style).is_err());
+    }
 }
@@ -0,0 +1,281 @@
+use std::mem;
+
+use crate::color::*;
+use crate::error::*;
+use crate::format::*;
+use crate::spinner::*;
+use crate::style::*;
+
+/// Parse the template of `ProgressBarStyle::from_template` into the layout.
+pub(crate) fn parse_template(template: &str) -> Result<Vec<Component>> {
+    let mut layout = vec![];
+    let mut text = String::new();
+    let mut chars = template.chars().enumerate().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek().map(|&(_, next)| next) == Some(c) => {
+                chars.next();
+                text.push(c);
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) => placeholder.push(c),
+                        None => return Err(invalid(format!("unclosed '{{' at column {}", i + 1))),
+                    }
+                }
+                if !text.is_empty() {
+                    layout.push(Component::Str(mem::take(&mut text)));
+                }
+                layout.push(parse_placeholder(&placeholder)?);
+            }
+            '}' => return Err(invalid(format!("unmatched '}}' at column {}", i + 1))),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        layout.push(Component::Str(text));
+    }
+    Ok(layout)
+}
+
+/// Parse a placeholder like `name:<10.cyan.bold`, without the braces.
+fn parse_placeholder(placeholder: &str) -> Result<Component> {
+    let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+    let (width, styles) = spec.split_at(spec.find(['.', '/']).unwrap_or(spec.len()));
+    let (style, empty) = match styles.split_once('/') {
+        Some((style, empty)) => (style, Some(empty)),
+        None => (styles, None),
+    };
+    let style = parse_text_style(style, placeholder)?;
+    let empty = empty
+        .map(|empty| parse_text_style(empty, placeholder))
+        .transpose()?;
+
+    let component = match name {
+        "bar" | "smooth_bar" => {
+            let width = match width {
+                "" => BarWidth::Fixed(30),
+                "fill" => BarWidth::Fill,
+                width => BarWidth::Fixed(parse_width(width, placeholder)?),
+            };
+            match name {
+                "bar" => Component::Bar(BarSymbols::default(), width),
+                _ => Component::SmoothBar(width),
+            }
+        }
+        name => {
+            if empty.is_some() {
+                return Err(invalid(format!(
+                    "the style of the empty part is only for bars, in '{{{}}}'",
+                    placeholder
+                )));
+            }
+            let component = match name {
+                "title" => Component::Title,
+                "pos" => Component::Position(UnitFormat::Default),
+                "len" => Component::Total(UnitFormat::Default),
+                "bytes" => Component::Position(UnitFormat::Bytes),
+                "total_bytes" => Component::Total(UnitFormat::Bytes),
+                "counter" => Component::Counter("/".to_string(), UnitFormat::Default),
+                "percent" => Component::PercentValue,
+                "eta" => Component::TimeLeft(TimeFormat::Fmt1),
+                "elapsed" => Component::TimeElapsed(TimeFormat::Fmt1),
+                "duration" => Component::TimeTotal(TimeFormat::Fmt1),
+                "speed" => Component::Speed(UnitFormat::Default),
+                "bytes_per_sec" => Component::Speed(UnitFormat::Bytes),
+                "msg" => Component::Message,
+                "spinner" => Component::Spinner(Spinner::default()),
+                _ => {
+                    return Err(invalid(format!(
+                        "unknown placeholder '{{{}}}'",
+                        placeholder
+                    )))
+                }
+            };
+            match parse_alignment(width, placeholder)? {
+                Some((alignment, width)) => {
+                    Component::Padded(Box::new(component), alignment, width)
+                }
+                None => component,
+            }
+        }
+    };
+
+    match (style == TextStyle::default(), empty) {
+        (true, None) => Ok(component),
+        (_, empty) => Ok(Component::Styled(Box::new(component), style, empty)),
+    }
+}
+
+/// Parse the alignment and the width like `>8`, the alignment is left by default.
+fn parse_alignment(s: &str, placeholder: &str) -> Result<Option<(Alignment, usize)>> {
+    let (alignment, width) = match s.chars().next() {
+        None => return Ok(None),
+        Some('<') => (Alignment::Left, &s[1..]),
+        Some('^') => (Alignment::Center, &s[1..]),
+        Some('>') => (Alignment::Right, &s[1..]),
+        Some(_) => (Alignment::Left, s),
+    };
+    Ok(Some((alignment, parse_width(width, placeholder)?)))
+}
+
+fn parse_width(s: &str, placeholder: &str) -> Result<usize> {
+    s.parse()
+        .map_err(|_| invalid(format!("invalid width '{}' in '{{{}}}'", s, placeholder)))
+}
+
+/// Parse the style like `.green.on_black.bold`.
+fn parse_text_style(s: &str, placeholder: &str) -> Result<TextStyle> {
+    let mut style = TextStyle::new();
+    for token in s.split('.').filter(|token| !token.is_empty()) {
+        style = match token {
+            "bold" => style.bold(),
+            "dim" => style.dim(),
+            "italic" => style.italic(),
+            token => match token.strip_prefix("on_") {
+                Some(color) => parse_color(color).map(|color| style.bg(color)),
+                None => parse_color(token).map(|color| style.fg(color)),
+            }
+            .ok_or_else(|| {
+                invalid(format!(
+                    "unknown style '{}' in '{{{}}}'",
+                    token, placeholder
+                ))
+            })?,
+        };
+    }
+    Ok(style)
+}
+
+/// Parse a color name, a number of the 256 colors, or a true color like `#ff8000`.
+fn parse_color(s: &str) -> Option<Color> {
+    let color = match s {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "bright_black" => Color::BrightBlack,
+        "bright_red" => Color::BrightRed,
+        "bright_green" => Color::BrightGreen,
+        "bright_yellow" => Color::BrightYellow,
+        "bright_blue" => Color::BrightBlue,
+        "bright_magenta" => Color::BrightMagenta,
+        "bright_cyan" => Color::BrightCyan,
+        "bright_white" => Color::BrightWhite,
+        s => match s.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 => {
+                let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+                Color::Rgb(channel(0)?, channel(2)?, channel(4)?)
+            }
+            Some(_) => return None,
+            None => Color::Fixed(s.parse().ok()?),
+        },
+    };
+    Some(color)
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidTemplate(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(layout: &[Component]) -> Vec<String> {
+        layout
+            .iter()
+            .map(|component| match component {
+                Component::Str(s) => format!("{:?}", s),
+                Component::Styled(component, ..) => {
+                    format!("styled {}", names(&[*component.clone()])[0])
+                }
+                Component::Padded(component, alignment, width) => format!(
+                    "{:?} {} {}",
+                    alignment,
+                    width,
+                    names(&[*component.clone()])[0]
+                ),
+                Component::Title => "title".to_string(),
+                Component::Bar(_, width) => format!("bar {:?}", width),
+                Component::Position(_) => "pos".to_string(),
+                Component::Total(_) => "len".to_string(),
+                Component::PercentValue => "percent".to_string(),
+                Component::TimeLeft(_) => "eta".to_string(),
+                _ => "other".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_template() {
+        let layout =
+            parse_template("{title} {bar:40.green/dim} {pos:>5}/{len} {percent}% eta {eta}")
+                .unwrap();
+        assert_eq!(
+            names(&layout),
+            vec![
+                "title",
+                "\" \"",
+                "styled bar Fixed(40)",
+                "\" \"",
+                "Right 5 pos",
+                "\"/\"",
+                "len",
+                "\" \"",
+                "percent",
+                "\"% eta \"",
+                "eta",
+            ]
+        );
+        match &layout[2] {
+            Component::Styled(_, style, empty) => {
+                assert_eq!(*style, TextStyle::new().fg(Color::Green));
+                assert_eq!(*empty, Some(TextStyle::new().dim()));
+            }
+            _ => panic!("expected styled bar"),
+        }
+
+        let layout = parse_template("{{{msg:.#ff8000.on_208}}}{bar:fill}").unwrap();
+        assert_eq!(names(&layout)[0], "\"{\"");
+        assert_eq!(names(&layout)[2], "\"}\"");
+        assert_eq!(names(&layout)[3], "bar Fill");
+        match &layout[1] {
+            Component::Styled(_, style, None) => assert_eq!(
+                *style,
+                TextStyle::new()
+                    .fg(Color::Rgb(255, 128, 0))
+                    .bg(Color::Fixed(208))
+            ),
+            _ => panic!("expected styled message"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        let reason = |template| match parse_template(template) {
+            Err(Error::InvalidTemplate(reason)) => reason,
+            _ => panic!("expected invalid template"),
+        };
+        assert_eq!(reason("{bar"), "unclosed '{' at column 1");
+        assert_eq!(reason("pos}"), "unmatched '}' at column 4");
+        assert_eq!(reason("{foo}"), "unknown placeholder '{foo}'");
+        assert_eq!(reason("{bar:4x}"), "invalid width '4x' in '{bar:4x}'");
+        assert_eq!(
+            reason("{eta:.gren}"),
+            "unknown style 'gren' in '{eta:.gren}'"
+        );
+        assert_eq!(
+            reason("{eta:.red/dim}"),
+            "the style of the empty part is only for bars, in '{eta:.red/dim}'"
+        );
+    }
+}
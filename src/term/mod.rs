@@ -1,3 +1,4 @@
+use std::env;
 use std::io::{self, Write};
 
 // FIXME
@@ -15,6 +16,28 @@ pub const TERM_DEFAULT_WIDTH: usize = 79;
 pub const TERM_DEFAULT_HEIGHT: usize = 5;
 pub const TERM_DEFAULT_WINDOW: (usize, usize) = (TERM_DEFAULT_WIDTH, TERM_DEFAULT_HEIGHT);
 
+/// Return whether the locale is UTF-8, according to `LC_ALL`, `LC_CTYPE` and `LANG`.
+///
+/// The locale is supposed to be UTF-8 on Windows if none of them is set.
+pub fn is_unicode_locale() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => cfg!(windows),
+    }
+}
+
+/// Return whether the terminal named by `TERM` can't move the cursor, like `TERM=dumb`.
+fn is_dumb(term: Option<&str>) -> bool {
+    term == Some("dumb")
+}
+
 enum TermTargetKind {
     Stdout,
     Stderr,
@@ -37,6 +60,25 @@ impl Term {
         }
     }
 
+    /// Return whether the target is attached to a terminal.
+    pub fn is_tty(&self) -> bool {
+        is_tty(self)
+    }
+
+    /// Return whether the target is a terminal that can redraw lines in place,
+    /// which is not the case for `TERM=dumb`.
+    pub fn is_interactive(&self) -> bool {
+        self.is_tty() && !is_dumb(env::var("TERM").ok().as_deref())
+    }
+
+    /// Return whether the target draws ANSI colors and attributes,
+    /// which are disabled by a non-empty `NO_COLOR`.
+    pub fn supports_color(&self) -> bool {
+        self.is_interactive()
+            && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            && enable_ansi(self)
+    }
+
     pub fn terminal_size(&self) -> Option<(usize, usize)> {
         terminal_size(self)
     }
@@ -49,6 +91,11 @@ impl Term {
         move_cursor_down(self, n)
     }
 
+    /// Clear the current line and move the cursor to the beginning of the line.
+    pub fn clear_line(&self) -> io::Result<()> {
+        clear_line(self)
+    }
+
     pub fn write_target(&self, bytes: &[u8]) -> io::Result<()> {
         match self.kind {
             TermTargetKind::Stdout => {
@@ -83,6 +130,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dumb_terminal() {
+        assert!(is_dumb(Some("dumb")));
+        assert!(!is_dumb(Some("xterm-256color")));
+        assert!(!is_dumb(Some("")));
+        assert!(!is_dumb(None));
+    }
+
     /*
     #[test]
     fn test_move_cursor_up() {
@@ -12,11 +12,16 @@ impl AsRawFd for Term {
     }
 }
 
+pub fn is_tty(term: &Term) -> bool {
+    unsafe { libc::isatty(term.as_raw_fd()) == 1 }
+}
+
+pub fn enable_ansi(_term: &Term) -> bool {
+    true
+}
+
 pub fn terminal_size(term: &Term) -> Option<(usize, usize)> {
-    match get_win_size(term.as_raw_fd()) {
-        Some((_, winsz)) => Some((winsz.ws_col as usize, winsz.ws_row as usize)),
-        None => None,
-    }
+    get_win_size(term.as_raw_fd()).map(|(_, winsz)| (winsz.ws_col as usize, winsz.ws_row as usize))
 }
 
 pub fn move_cursor_up(term: &Term, n: usize) -> io::Result<()> {
@@ -27,6 +32,10 @@ pub fn move_cursor_down(term: &Term, n: usize) -> io::Result<()> {
     term.write_target(format!("\x1b[{}B", n).as_bytes())
 }
 
+pub fn clear_line(term: &Term) -> io::Result<()> {
+    term.write_target(b"\r\x1b[2K")
+}
+
 fn get_win_size(handle: RawFd) -> Option<(RawFd, libc::winsize)> {
     let mut winsz = libc::winsize {
         ws_row: 0,
@@ -2,16 +2,17 @@ use std::io;
 use std::os::windows::io::{AsRawHandle, RawHandle};
 
 use winapi::um::{
+    consoleapi::{GetConsoleMode, SetConsoleMode},
     processenv::GetStdHandle,
     winbase::{STD_ERROR_HANDLE, STD_OUTPUT_HANDLE},
     wincon::{
         GetConsoleScreenBufferInfo, SetConsoleCursorPosition, CONSOLE_SCREEN_BUFFER_INFO, COORD,
-        SMALL_RECT,
+        ENABLE_VIRTUAL_TERMINAL_PROCESSING, SMALL_RECT,
     },
     winnt::HANDLE,
 };
 
-use crate::term::{Term, TermTargetKind};
+use crate::term::{Term, TermTargetKind, TERM_DEFAULT_WIDTH};
 
 impl AsRawHandle for Term {
     fn as_raw_handle(&self) -> RawHandle {
@@ -22,6 +23,22 @@ impl AsRawHandle for Term {
     }
 }
 
+pub fn is_tty(term: &Term) -> bool {
+    let mut mode = 0;
+    unsafe { GetConsoleMode(term.as_raw_handle() as HANDLE, &mut mode) != 0 }
+}
+
+/// Enable the escape sequences on the console, legacy consoles don't support them.
+pub fn enable_ansi(term: &Term) -> bool {
+    let handle = term.as_raw_handle() as HANDLE;
+    let mut mode = 0;
+    unsafe {
+        GetConsoleMode(handle, &mut mode) != 0
+            && (mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0)
+    }
+}
+
 pub fn terminal_size(term: &Term) -> Option<(usize, usize)> {
     match get_console_screen_buffer_info(term.as_raw_handle()) {
         Some((_, csbi)) => Some((
@@ -70,6 +87,16 @@ pub fn move_cursor_down(term: &Term, n: usize) -> io::Result<()> {
     }
 }
 
+/// Clear the line with the escape sequence if the console supports it,
+/// legacy consoles wrap once the last column is filled, so it's left untouched.
+pub fn clear_line(term: &Term) -> io::Result<()> {
+    if enable_ansi(term) {
+        return term.write_target(b"\r\x1b[2K");
+    }
+    let width = terminal_size(term).map_or(TERM_DEFAULT_WIDTH, |(w, _)| w);
+    term.write_target(format!("\r{}\r", " ".repeat(width.saturating_sub(1))).as_bytes())
+}
+
 fn get_console_screen_buffer_info(
     handle: RawHandle,
 ) -> Option<(RawHandle, CONSOLE_SCREEN_BUFFER_INFO)> {
@@ -1,13 +1,18 @@
 use std::time::Duration;
 
+use unicode_width::UnicodeWidthChar;
+
+use crate::style::Alignment;
+
 const NANOS_PER_SEC: f64 = 1e9;
 
 pub fn duration_to_secs(d: Duration) -> f64 {
     d.as_secs() as f64 + f64::from(d.subsec_nanos()) / NANOS_PER_SEC
 }
 
-pub fn secs_to_duration(s: f64) -> Duration {
-    Duration::new(s.trunc() as u64, (s.fract() * NANOS_PER_SEC) as u32)
+/// Return `None` if `s` is negative, not finite, or too large for a `Duration`.
+pub fn secs_to_duration(s: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(s).ok()
 }
 
 pub fn duration_to_datetime(d: Duration) -> (u64, u64, u64, u64) {
@@ -34,6 +39,75 @@ pub fn duration_to_datetime(d: Duration) -> (u64, u64, u64, u64) {
     }
 }
 
+/// Return the number of terminal columns taken by `s`, wide characters take two columns,
+/// control and zero-width characters and ANSI escape sequences take none.
+pub fn display_width(s: &str) -> usize {
+    visible_chars(s).map(|(_, c)| char_width(c)).sum()
+}
+
+/// Cut `s` to at most `width` columns, a wide character crossing the limit is dropped.
+///
+/// The escape sequences are kept, and the text style is reset if `s` is cut.
+pub fn truncate_to_width(s: &str, width: usize) -> String {
+    let mut used = 0;
+    for (i, c) in visible_chars(s) {
+        used += char_width(c);
+        if used > width {
+            return match s[..i].contains('\x1b') {
+                true => format!("{}\x1b[0m", &s[..i]),
+                false => s[..i].to_string(),
+            };
+        }
+    }
+    s.to_string()
+}
+
+/// Iterate the characters with their indices, skipping the ANSI escape sequences like "\x1b[1;32m".
+fn visible_chars(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut escaped = false;
+    let mut chars = s.char_indices().peekable();
+    std::iter::from_fn(move || loop {
+        let (i, c) = chars.next()?;
+        match c {
+            '\x1b' if chars.peek().map(|&(_, c)| c) == Some('[') => {
+                chars.next();
+                escaped = true;
+            }
+            // the final byte of the sequence.
+            '@'..='~' if escaped => escaped = false,
+            _ if escaped => {}
+            _ => return Some((i, c)),
+        }
+    })
+}
+
+/// Pad `s` with spaces to `width` columns, `s` is unchanged if it's wider.
+pub fn pad_to_width(s: &str, alignment: Alignment, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    let (left, right) = match alignment {
+        Alignment::Left => (0, padding),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::Right => (padding, 0),
+    };
+    format!("{}{}{}", " ".repeat(left), s, " ".repeat(right))
+}
+
+/// Repeat `s` to fill `width` columns, the columns left by a wide symbol are spaces,
+/// and an empty `s` is filled with spaces.
+pub fn repeat_to_width(s: &str, width: usize) -> String {
+    let w = display_width(s);
+    if w == 0 {
+        return " ".repeat(width);
+    }
+    let mut out = s.repeat(width / w);
+    out += &" ".repeat(width % w);
+    out
+}
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,7 +115,25 @@ mod tests {
     #[test]
     fn test_duration_convert() {
         let d = Duration::new(1, 234);
-        assert_eq!(secs_to_duration(duration_to_secs(d)), d)
+        assert_eq!(secs_to_duration(duration_to_secs(d)), Some(d))
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("\rabc "), 4);
+        assert_eq!(display_width("进度"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(truncate_to_width("a进度", 2), "a");
+        assert_eq!(truncate_to_width("a进度", 3), "a进");
+        assert_eq!(repeat_to_width("进", 5), "进进 ");
+        assert_eq!(display_width("\x1b[1;32m##\x1b[0m-"), 3);
+        assert_eq!(pad_to_width("进", Alignment::Center, 5), " 进  ");
+        assert_eq!(pad_to_width("42", Alignment::Right, 1), "42");
+        assert_eq!(
+            truncate_to_width("\x1b[32m###\x1b[0m", 2),
+            "\x1b[32m##\x1b[0m"
+        );
+        assert_eq!(repeat_to_width("█", 3), "███");
     }
 
     #[test]